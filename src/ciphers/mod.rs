@@ -8,6 +8,7 @@ pub use crate::ciphers::action::CipherAction;
 
 use anyhow::Result;
 use rand::Rng;
+use sha2::{Digest, Sha256};
 use xsalsa20poly1305::aead::{Aead, KeyInit};
 use xsalsa20poly1305::aead::generic_array::{GenericArray, typenum};
 use xsalsa20poly1305::XSalsa20Poly1305;
@@ -16,6 +17,16 @@ pub fn generate() -> String {
     base64_url::encode(&rand::thread_rng().gen::<[u8; 32]>())
 }
 
+/// Returns sha256 hex digest of the value
+pub fn hash<T>(value: T) -> String
+    where T: ToString
+{
+    Sha256::digest(value.to_string().as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
 pub fn decrypt<C, K>(content: C, key: K) -> Result<String>
     where C: ToString,
           K: ToString
@@ -1,3 +1,84 @@
+use mongodb::bson::{oid::ObjectId, Bson, DateTime as BsonDateTime, Document};
+
+use crate::traits::FromBson;
+
 pub fn to_string_array<T: ToString>(value: &Option<Vec<T>>) -> Option<Vec<String>> {
     value.as_ref().map(|v| v.iter().filter_map(|item| if !item.to_string().is_empty() { Some(item.to_string()) } else { None }).collect())
-}
\ No newline at end of file
+}
+
+impl FromBson for Bson {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl FromBson for String {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        match value {
+            Bson::String(value) => Some(value.clone()),
+            Bson::ObjectId(value) => Some(value.to_hex()),
+            _ => None
+        }
+    }
+}
+
+impl FromBson for i32 {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        match value {
+            Bson::Int32(value) => Some(*value),
+            Bson::Int64(value) => i32::try_from(*value).ok(),
+            _ => None
+        }
+    }
+}
+
+impl FromBson for i64 {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        match value {
+            Bson::Int32(value) => Some(*value as i64),
+            Bson::Int64(value) => Some(*value),
+            _ => None
+        }
+    }
+}
+
+impl FromBson for f64 {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        match value {
+            Bson::Double(value) => Some(*value),
+            Bson::Int32(value) => Some(*value as f64),
+            Bson::Int64(value) => Some(*value as f64),
+            _ => None
+        }
+    }
+}
+
+impl FromBson for bool {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl FromBson for ObjectId {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        value.as_object_id()
+    }
+}
+
+impl FromBson for BsonDateTime {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        value.as_datetime().copied()
+    }
+}
+
+impl FromBson for Document {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        value.as_document().cloned()
+    }
+}
+
+impl<T: FromBson> FromBson for Vec<T> {
+    fn from_bson(value: &Bson) -> Option<Self> {
+        value.as_array()?.iter().map(T::from_bson).collect()
+    }
+}
@@ -7,7 +7,7 @@ pub use array::MongoArray;
 pub use datetime::MongoDateTime;
 pub use objectid::MongoObjectId;
 
-use mongodb::{Client, options::ClientOptions, Database};
+use mongodb::{bson::doc, Client, options::ClientOptions, Database};
 use std::env;
 
 use crate::Payload;
@@ -77,6 +77,15 @@ impl MongoDBManager {
         }
     }
 
+    pub async fn ping(&self) -> Result<()> {
+        let database = self.get()?;
+
+        match database.run_command(doc! { "ping": 1 }, None).await {
+            Ok(_) => Ok(()),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+
     pub fn get_client(&self) -> Result<Client> {
         match self {
             Self::MongoDB(value) => Ok(value.client.clone()),
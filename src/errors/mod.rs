@@ -0,0 +1,96 @@
+use actix_web::error;
+use std::fmt;
+
+use crate::Payload;
+
+/// Crate error with a stable machine readable code, converts into `Payload` with the
+/// matching status so handlers can return `Err(ApiError::NotFound(..).into())`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    AuthExpired(String),
+    AuthInvalid(String),
+    Forbidden(String),
+    NotFound(String),
+    Conflict(String),
+    Validation(String),
+    RateLimited(String),
+    Database(String),
+    Storage(String),
+    Mail(String),
+    Config(String),
+    Internal(String),
+}
+
+impl ApiError {
+    /// Returns stable error code, clients should match on this instead of the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::AuthExpired(_) => "auth_expired",
+            Self::AuthInvalid(_) => "auth_invalid",
+            Self::Forbidden(_) => "forbidden",
+            Self::NotFound(_) => "not_found",
+            Self::Conflict(_) => "conflict",
+            Self::Validation(_) => "validation",
+            Self::RateLimited(_) => "rate_limited",
+            Self::Database(_) => "database",
+            Self::Storage(_) => "storage",
+            Self::Mail(_) => "mail",
+            Self::Config(_) => "config",
+            Self::Internal(_) => "internal",
+        }
+    }
+
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::AuthExpired(_) | Self::AuthInvalid(_) => 401,
+            Self::Forbidden(_) => 403,
+            Self::NotFound(_) => 404,
+            Self::Conflict(_) => 409,
+            Self::Validation(_) => 400,
+            Self::RateLimited(_) => 429,
+            Self::Storage(_) | Self::Mail(_) => 502,
+            Self::Database(_) | Self::Config(_) | Self::Internal(_) => 500,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            Self::AuthExpired(message) |
+            Self::AuthInvalid(message) |
+            Self::Forbidden(message) |
+            Self::NotFound(message) |
+            Self::Conflict(message) |
+            Self::Validation(message) |
+            Self::RateLimited(message) |
+            Self::Database(message) |
+            Self::Storage(message) |
+            Self::Mail(message) |
+            Self::Config(message) |
+            Self::Internal(message) => message.clone()
+        }
+    }
+}
+
+// Implement display for api error
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl From<ApiError> for Payload {
+    fn from(error: ApiError) -> Self {
+        Self {
+            code: Some(error.status()),
+            error: Some(error.message()),
+            error_code: Some(error.code().to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ApiError> for error::Error {
+    fn from(error: ApiError) -> Self {
+        Payload::from(error).into()
+    }
+}
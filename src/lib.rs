@@ -3,8 +3,11 @@ pub mod ciphers;
 pub mod conversions;
 pub mod cursors;
 pub mod databases;
+pub mod errors;
 pub mod generators;
+pub mod locales;
 pub mod mailers;
+pub mod monitors;
 pub mod middlewares;
 pub mod numbers;
 pub mod parsers;
@@ -14,6 +17,7 @@ pub mod pipelines;
 pub mod placeholders;
 pub mod primitives;
 pub mod s3;
+pub mod sessions;
 pub mod schedulers;
 pub mod settings;
 pub mod stages;
@@ -24,12 +28,24 @@ pub mod validators;
 
 pub use ciphers::Cipher;
 pub use cursors::CustomCursor;
+pub use errors::ApiError;
+pub use locales::Locale;
+pub use locales::Locales;
+pub use locales::Message;
 pub use mailers::Mailer;
 pub use paseto::Paseto;
 pub use payloads::Payload;
+pub use pipelines::Filter;
+pub use pipelines::ListQuery;
+pub use pipelines::ListQueryOptions;
+pub use pipelines::Page;
+pub use pipelines::PageCursor;
 pub use pipelines::Pipeline;
+pub use pipelines::Visibility;
+pub use pipelines::VisibilityClaims;
 pub use primitives::Primitive;
 pub use s3::S3;
+pub use sessions::Session;
 pub use schedulers::Scheduler;
 pub use settings::Settings;
 pub use swaps::Swap;
@@ -39,11 +55,20 @@ pub use databases::array::MongoArray;
 pub use databases::datetime::MongoDateTime;
 pub use databases::objectid::MongoObjectId;
 
+pub use middlewares::AccessLogger;
+pub use middlewares::ContentSecurityPolicy;
 pub use middlewares::CoreGuard;
+pub use middlewares::CsrfGuard;
 pub use middlewares::PermissionGuard;
 pub use middlewares::PermissionGuardOptions;
 pub use middlewares::PermissionGuardParams;
+pub use middlewares::ProblemNegotiator;
+pub use middlewares::RequestId;
+pub use middlewares::RequestTracker;
+pub use middlewares::SecurityHeaders;
+pub use middlewares::TrustedProxies;
 pub use middlewares::UserAgent;
+pub use middlewares::UserAgentClassification;
 pub use middlewares::UserAgentParser;
 
 pub use placeholders::Account;
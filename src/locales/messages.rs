@@ -0,0 +1,202 @@
+use std::fmt;
+
+/// Built in english messages of the crate, catalogs override them per locale using the same keys
+pub static MESSAGES: &[(&str, &str)] = &[
+    // Authentication
+    ("auth.expired", "Authentication token expired"),
+    ("auth.token_invalid", "Invalid authentication token"),
+    ("auth.token_expired", "Your authentication token has expired"),
+    ("auth.access_token_failed", "Unable to generate access token"),
+    ("auth.refresh_token_failed", "Unable to generate refresh token"),
+    ("auth.refresh_token_invalid", "Invalid refresh token"),
+    ("auth.refresh_token_expired", "Your refresh token has expired"),
+    ("auth.encryption_failed", "Encryption failed"),
+    ("auth.decryption_failed", "Decryption failed"),
+    ("auth.csrf_invalid", "Invalid or missing CSRF token"),
+
+    // Sessions
+    ("session.revoked", "Your session has expired or has been revoked"),
+    ("session.save_failed", "An error occurred while trying to save a new session"),
+
+    // Requests and server setup
+    ("request.invalid_object_id", "Invalid object id"),
+    ("request.record_not_found", "No matching record was found in the database. Please check your input and try again"),
+    ("request.invalid_cursor", "Invalid pagination cursor"),
+    ("request.invalid_date", "Invalid date {value}"),
+    ("request.invalid_timezone", "Invalid timezone {timezone}"),
+    ("request.search_too_long", "Search value exceeds {max} characters"),
+    ("request.unreadable_document", "Unable to read document {id}: {error}"),
+    ("request.unreadable_field", "Unable to read {field} of document {id}"),
+    ("server.database_invalid", "Invalid database configuration"),
+    ("server.database_unavailable", "Database is unavailable, please try again later"),
+    ("server.middleware_missing", "Missing middleware. Please configure your server properly"),
+
+    // List query validation of `ListQuery`
+    ("list.invalid", "invalid"),
+    ("list.invalid_filter", "invalid filter"),
+    ("list.invalid_id", "invalid id"),
+    ("list.unknown_operator", "unknown operator {operator}"),
+    ("list.not_sortable", "{field} is not sortable"),
+    ("list.not_filterable", "{field} is not filterable"),
+    ("list.operator_not_allowed", "{operator} is not allowed on {field}"),
+    ("list.search_not_allowed", "not allowed"),
+    ("list.maximum", "maximum of {max} characters allowed"),
+    ("list.expected_integer", "expected an integer"),
+    ("list.expected_number", "expected a number"),
+    ("list.expected_boolean", "expected true or false"),
+    ("list.expected_date", "expected an RFC 3339 date"),
+
+    // Password rules of `validate_password`
+    ("password.minimum", "minimum of {min} characters allowed"),
+    ("password.maximum", "maximum of {max} characters allowed"),
+    ("password.number", "at least 1 number"),
+    ("password.uppercase", "at least 1 uppercase character"),
+    ("password.lowercase", "at least 1 lowercase character"),
+    ("password.special_character", "at least 1 special character"),
+
+    // Settings
+    ("settings.api_url.empty", "API URL cannot be empty"),
+    ("settings.api_url.invalid", "API URL is invalid"),
+    ("settings.web_url.empty", "Web URL cannot be empty"),
+    ("settings.web_url.invalid", "Web URL is invalid"),
+    ("settings.admin_url.empty", "Admin URL cannot be empty"),
+    ("settings.admin_url.invalid", "Admin URL is invalid"),
+    ("settings.access_token_key_unit.empty", "Please enter an access token key unit"),
+    ("settings.access_token_key_unit.invalid", "Please enter a valid access token key unit"),
+    ("settings.access_token_key_time.empty", "Please enter your access token key time"),
+    ("settings.access_token_key_time.invalid", "Please enter a valid access token key time"),
+    ("settings.access_token_key_signing.empty", "Please enter your access token key signing"),
+    ("settings.access_token_key_signing.invalid", "Please enter a valid access token key signing"),
+    ("settings.refresh_token_key_unit.empty", "Please enter an refresh token key unit"),
+    ("settings.refresh_token_key_unit.invalid", "Please enter a valid refresh token key unit"),
+    ("settings.refresh_token_key_time.empty", "Please enter your refresh token key time"),
+    ("settings.refresh_token_key_time.invalid", "Please enter a valid refresh token key time"),
+    ("settings.refresh_token_key_signing.empty", "Please enter your refresh token key signing"),
+    ("settings.refresh_token_key_signing.invalid", "Please enter a valid refresh token key signing"),
+    ("settings.sender.empty", "Please enter the sender of your mailer"),
+    ("settings.sender.invalid", "Please enter a valid sender"),
+    ("settings.username.empty", "Please enter the mailer username"),
+    ("settings.username.invalid", "Please enter a valid username"),
+    ("settings.password.empty", "Please enter the mailer password"),
+    ("settings.password.invalid", "Please enter a valid password"),
+    ("settings.smtp_host.empty", "Please enter the mailer SMTP host"),
+    ("settings.smtp_host.invalid", "Please enter a valid SMTP host"),
+    ("settings.service.empty", "Please enter the mailer service"),
+    ("settings.service.invalid", "Please enter a valid mailer services"),
+    ("settings.email.empty", "Please enter the mailer service"),
+    ("settings.email.invalid", "Please enter a valid mailer services"),
+    ("settings.access_key_id.empty", "Please enter an access key id"),
+    ("settings.access_key_id.invalid", "Please enter a valid access key id"),
+    ("settings.secret_access_key.empty", "Please enter a secret access key"),
+    ("settings.secret_access_key.invalid", "Please enter a valid secret access key"),
+    ("settings.bucket.empty", "Please enter a bucket name"),
+    ("settings.bucket.invalid", "Please enter a valid bucket name"),
+    ("settings.region.empty", "Please enter a region"),
+    ("settings.region.invalid", "Please enter a valid region"),
+];
+
+/// Returns the built in english message of a key
+pub fn default_message(key: &str) -> Option<&'static str> {
+    MESSAGES
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, message)| *message)
+}
+
+/// Message key with named arguments e.g. `Message::new("password.minimum").set_arg("min", 8)`.
+/// Payloads carry it as `password.minimum?min=8` and it is rendered with the negotiated
+/// catalog when the response is built, strings that aren't known keys are sent as they are
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub key: String,
+    pub args: Vec<(String, String)>,
+}
+
+impl Message {
+    pub fn new<T>(key: T) -> Self
+        where T: ToString
+    {
+        Self {
+            key: key.to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    pub fn set_arg<N, V>(&self, name: N, value: V) -> Self
+        where N: ToString,
+              V: ToString
+    {
+        let mut data = self.clone();
+        data.args.retain(|(n, _)| *n != name.to_string());
+        data.args.push((name.to_string(), value.to_string()));
+        data
+    }
+
+    /// Parses `key` or `key?name=value&...`, returns None for anything that isn't a message key
+    pub fn parse(value: &str) -> Option<Self> {
+        let (key, args) = match value.split_once('?') {
+            Some((key, args)) => (key, Some(args)),
+            None => (value, None)
+        };
+
+        let is_key = key.contains('.')
+            && !key.starts_with('.')
+            && !key.ends_with('.')
+            && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.');
+
+        if !is_key {
+            return None;
+        }
+
+        let mut data = Self::new(key);
+
+        for pair in args.unwrap_or_default().split('&').filter(|pair| !pair.is_empty()) {
+            let (name, value) = pair.split_once('=')?;
+            data.args.push((decode(name), decode(value)));
+        }
+
+        Some(data)
+    }
+
+    /// Fills `{name}` placeholders of a template with the arguments
+    pub fn render(&self, template: &str) -> String {
+        let mut message = template.to_string();
+        for (name, value) in &self.args {
+            message = message.replace(&format!("{{{name}}}"), value);
+        }
+
+        message
+    }
+
+    /// Renders the built in english message, or the key when there's none
+    pub fn to_text(&self) -> String {
+        match default_message(&self.key) {
+            Some(template) => self.render(template),
+            None => self.to_string()
+        }
+    }
+}
+
+// Implement display for message, this is the form carried by payloads
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.key)?;
+
+        for (index, (name, value)) in self.args.iter().enumerate() {
+            let separator = if index == 0 { '?' } else { '&' };
+            write!(f, "{separator}{}={}", encode(name), encode(value))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Escapes argument separators
+fn encode(value: &str) -> String {
+    value.replace('%', "%25").replace('&', "%26").replace('=', "%3D")
+}
+
+// Reverses `encode`
+fn decode(value: &str) -> String {
+    value.replace("%3D", "=").replace("%26", "&").replace("%25", "%")
+}
@@ -0,0 +1,232 @@
+pub mod messages;
+
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, Result};
+use actix_web::dev::Payload as RequestPayload;
+use actix_web::web::Data;
+use futures::future::{ok, Ready};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::Payload;
+
+pub use messages::Message;
+
+/// Locale of the built in messages, used when no default locale is configured
+pub static DEFAULT_LOCALE: &str = "en";
+
+// Create locale object, resolved from `Accept-Language`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locale(pub String);
+
+/// Locales holds translation catalogs keyed by locale. Catalogs map message keys (see
+/// `messages::MESSAGES`) to templates with `{name}` placeholders e.g.
+/// `{ "password.minimum": "mínimo de {min} caracteres" }`. Keys missing from a catalog fall back
+/// to the default locale and then the built in english message. Register it as app data
+/// (`Data<Locales>` or `Locales`) so `Payload` responses and `Locale` use it
+#[derive(Debug, Clone)]
+pub struct Locales {
+    pub default_locale: String,
+    catalogs: HashMap<String, HashMap<String, String>>,
+}
+
+impl Default for Locales {
+    fn default() -> Self {
+        Self {
+            default_locale: String::from(DEFAULT_LOCALE),
+            catalogs: HashMap::new(),
+        }
+    }
+}
+
+impl Locales {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    /// Retrieves locales registered as app data
+    pub fn from_request(req: &HttpRequest) -> Option<&Self> {
+        req.app_data::<Data<Self>>()
+            .map(|locales| locales.get_ref())
+            .or(req.app_data::<Self>())
+    }
+
+    /// Loads every `<locale>.json` file (flat object of message key to template) in directory
+    pub fn from_directory<T: AsRef<Path>>(directory: T) -> Result<Self> {
+        let entries = match fs::read_dir(directory.as_ref()) {
+            Ok(entries) => entries,
+            Err(error) => return Err(Payload::error(error))
+        };
+
+        let mut data = Self::default();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+
+            let locale = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(locale) => locale.to_string(),
+                None => continue
+            };
+
+            let content = match fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(error) => return Err(Payload::error(error))
+            };
+
+            match serde_json::from_str::<HashMap<String, String>>(&content) {
+                Ok(catalog) => data = data.set_catalog(locale, catalog),
+                Err(error) => return Err(Payload::error(format!("Invalid locale file {}: {error}", path.display())))
+            }
+        }
+
+        Ok(data)
+    }
+
+    pub fn set_default_locale<T>(&self, default_locale: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.default_locale = normalize(&default_locale.to_string());
+        data
+    }
+
+    pub fn set_catalog<T>(&self, locale: T, catalog: HashMap<String, String>) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.catalogs
+            .entry(normalize(&locale.to_string()))
+            .or_default()
+            .extend(catalog);
+
+        data
+    }
+
+    pub fn get_locales(&self) -> Vec<String> {
+        self.catalogs.keys().cloned().collect()
+    }
+
+    /// Picks the best available locale for an `Accept-Language` header value
+    pub fn negotiate(&self, accept_language: &str) -> String {
+        let mut ranges = accept_language
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = normalize(parts.next()?.trim());
+                let q = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+
+                match tag.is_empty() || q <= 0.0 {
+                    true => None,
+                    false => Some((tag, q))
+                }
+            })
+            .collect::<Vec<(String, f32)>>();
+
+        // Stable sort keeps header order for equal weights
+        ranges.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (tag, _) in ranges {
+            if tag == "*" {
+                break;
+            }
+
+            if tag == self.default_locale || self.catalogs.contains_key(&tag) {
+                return tag;
+            }
+
+            // Fall back from `pt-br` to `pt`
+            if let Some((primary, _)) = tag.split_once('-') {
+                if primary == self.default_locale || self.catalogs.contains_key(primary) {
+                    return primary.to_string();
+                }
+            }
+        }
+
+        self.default_locale.clone()
+    }
+
+    /// Renders a message key (see `Message`) in the locale, falling back to the default locale
+    /// catalog and the built in english message. Strings that aren't known keys are returned as is
+    pub fn translate<T>(&self, locale: &str, message: T) -> String
+        where T: ToString
+    {
+        let text = message.to_string();
+        let message = match Message::parse(&text) {
+            Some(message) => message,
+            None => return text
+        };
+
+        for locale in [normalize(locale), self.default_locale.clone()] {
+            if let Some(template) = self.catalogs.get(&locale).and_then(|catalog| catalog.get(&message.key)) {
+                return message.render(template);
+            }
+        }
+
+        match messages::default_message(&message.key) {
+            Some(template) => message.render(template),
+            None => text
+        }
+    }
+
+    /// Translates every string found in a json value, used for `errors` maps
+    pub fn translate_value(&self, locale: &str, value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::String(message) => serde_json::Value::String(self.translate(locale, message)),
+            serde_json::Value::Array(items) => serde_json::Value::Array(items
+                .iter()
+                .map(|item| self.translate_value(locale, item))
+                .collect()),
+            serde_json::Value::Object(map) => serde_json::Value::Object(map
+                .iter()
+                .map(|(key, item)| (key.clone(), self.translate_value(locale, item)))
+                .collect()),
+            _ => value.clone()
+        }
+    }
+}
+
+impl Locale {
+    /// Resolves locale from request extensions or `Accept-Language` using the registered
+    /// `Locales`, returns None when none is registered
+    pub fn resolve(req: &HttpRequest) -> Option<Self> {
+        if let Some(locale) = req.extensions().get::<Locale>() {
+            return Some(locale.clone());
+        }
+
+        let locales = Locales::from_request(req)?;
+        let accept_language = req
+            .headers()
+            .get("Accept-Language")
+            .and_then(|h| h.to_str().ok())
+            .unwrap_or_default();
+
+        let locale = Self(locales.negotiate(accept_language));
+        req.extensions_mut().insert(locale.clone());
+
+        Some(locale)
+    }
+}
+
+// Implement from request
+impl FromRequest for Locale {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut RequestPayload) -> Self::Future {
+        match Self::resolve(req) {
+            Some(locale) => ok(locale),
+            None => ok(Self(Locales::default().default_locale))
+        }
+    }
+}
+
+// Normalizes language tags e.g. `pt_BR` to `pt-br`
+fn normalize(tag: &str) -> String {
+    tag.trim().replace('_', "-").to_lowercase()
+}
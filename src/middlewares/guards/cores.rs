@@ -3,29 +3,111 @@ use actix_web::body::{EitherBody, MessageBody};
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
 use actix_web::http::Method;
 use actix_web::web::Data;
-use actix_utils::future::{Either, ok, Ready};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use futures::lock::Mutex as AsyncMutex;
 use handlebars::Handlebars;
+use std::rc::Rc;
 use std::task::{Context, Poll};
-use std::marker::PhantomData;
+use std::time::{Duration, Instant};
 use std::sync::{Arc, RwLock};
 
-use crate::middlewares::guards::AuthenticationFuture;
+use crate::Message;
 use crate::MongoDBManager;
 use crate::Paseto;
 use crate::Payload;
 
 /// Core guard checks for all core settings and configurations
 /// such as database connection, handlebars and paseto.
-/// This should always sit on the very top of wrap declaration in actix
+/// This should always sit on the very top of wrap declaration in actix.
+/// When ping is enabled the database is pinged and the result is cached for `ping_interval` seconds,
+/// only one request refreshes an expired result while the others wait for it.
+/// Register the same guard as app data so `monitors::health` shares its ping timeout
 #[warn(clippy::module_inception)]
-#[derive(Default)]
-pub struct CoreGuard {}
+#[derive(Clone)]
+pub struct CoreGuard {
+    pub is_ping: bool,
+    pub ping_interval: u64,
+    pub ping_timeout: u64,
+    ping_cache: Arc<RwLock<Option<(Instant, bool)>>>,
+    ping_lock: Arc<AsyncMutex<()>>,
+}
+
+impl Default for CoreGuard {
+    fn default() -> Self {
+        Self {
+            is_ping: false,
+            ping_interval: 30,
+            ping_timeout: 2,
+            ping_cache: Arc::new(RwLock::new(None)),
+            ping_lock: Arc::new(AsyncMutex::new(())),
+        }
+    }
+}
+
+impl CoreGuard {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn set_ping(&self, is_ping: bool) -> Self {
+        let mut data = self.clone();
+        data.is_ping = is_ping;
+        data
+    }
+
+    pub fn set_ping_interval(&self, ping_interval: u64) -> Self {
+        let mut data = self.clone();
+        data.ping_interval = ping_interval;
+        data
+    }
+
+    pub fn set_ping_timeout(&self, ping_timeout: u64) -> Self {
+        let mut data = self.clone();
+        data.ping_timeout = ping_timeout;
+        data
+    }
+
+    // Returns cached ping result if it hasn't expired yet
+    fn get_cached_ping(&self) -> Option<bool> {
+        let cache = self.ping_cache.read().ok()?;
+
+        cache.and_then(|(checked_at, is_up)| match checked_at.elapsed() < Duration::from_secs(self.ping_interval) {
+            true => Some(is_up),
+            false => None
+        })
+    }
+
+    // Pings database with the configured timeout
+    pub async fn ping(&self, database: &MongoDBManager) -> bool {
+        matches!(
+            actix_web::rt::time::timeout(Duration::from_secs(self.ping_timeout), database.ping()).await,
+            Ok(Ok(_))
+        )
+    }
+
+    // Pings database and caches the result, requests arriving during the ping reuse its result
+    async fn refresh_ping(&self, database: &MongoDBManager) -> bool {
+        let _lock = self.ping_lock.lock().await;
+
+        if let Some(is_up) = self.get_cached_ping() {
+            return is_up;
+        }
+
+        let is_up = self.ping(database).await;
+
+        if let Ok(mut cache) = self.ping_cache.write() {
+            *cache = Some((Instant::now(), is_up));
+        }
+
+        is_up
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for CoreGuard
     where
-        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
         S::Future: 'static,
-        B: MessageBody,
+        B: MessageBody + 'static,
 {
     type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
@@ -34,23 +116,27 @@ impl<S, B> Transform<S, ServiceRequest> for CoreGuard
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ok(CoreGuardMiddleware { service })
+        ok(CoreGuardMiddleware {
+            service: Rc::new(service),
+            options: self.clone(),
+        })
     }
 }
 
 pub struct CoreGuardMiddleware<S> {
-    pub service: S
+    pub service: Rc<S>,
+    pub options: CoreGuard,
 }
 
 impl<S, B> Service<ServiceRequest> for CoreGuardMiddleware<S>
     where
-        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
         S::Future: 'static,
-        B: MessageBody,
+        B: MessageBody + 'static,
 {
     type Response = ServiceResponse<EitherBody<B>>;
     type Error = Error;
-    type Future = Either<AuthenticationFuture<S, B>, Ready<Result<Self::Response, Self::Error>>>;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         self.service.poll_ready(cx)
@@ -58,53 +144,86 @@ impl<S, B> Service<ServiceRequest> for CoreGuardMiddleware<S>
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
         if Method::OPTIONS == *req.method() {
-            return Either::left(AuthenticationFuture {
-                fut: self.service.call(req),
-                _phantom: PhantomData,
+            let fut = self.service.call(req);
+
+            return Box::pin(async move {
+                Ok(fut.await?.map_into_left_body())
             });
         }
 
         // Check database access
         let payload = Payload::database();
-        match req.app_data::<Data<MongoDBManager>>() {
-            None => return Either::right(ok(req
+        let database = match req.app_data::<Data<MongoDBManager>>() {
+            None => return Box::pin(ok(req
                 .into_response(payload)
                 .map_into_boxed_body()
                 .map_into_right_body())),
             Some(database) => {
                 match database.get() {
-                    Ok(_) => {},
-                    Err(_) => return Either::right(ok(req
+                    Ok(_) => database.clone(),
+                    Err(_) => return Box::pin(ok(req
                         .into_response(payload)
                         .map_into_boxed_body()
                         .map_into_right_body()))
                 }
             }
-        }
+        };
 
         // Check for handlebars
         let payload = Payload::middleware();
-        match req.app_data::<Data<Handlebars<'_>>>() {
-            None => return Either::right(ok(req
+        if req.app_data::<Data<Handlebars<'_>>>().is_none() {
+            return Box::pin(ok(req
                 .into_response(payload)
                 .map_into_boxed_body()
-                .map_into_right_body())),
-            Some(_) => {}
+                .map_into_right_body()));
         }
 
         // Check paseto
-        match req.app_data::<Data<Arc<RwLock<Paseto>>>>() {
-            None => return Either::right(ok(req
+        if req.app_data::<Data<Arc<RwLock<Paseto>>>>().is_none() {
+            return Box::pin(ok(req
                 .into_response(payload)
                 .map_into_boxed_body()
-                .map_into_right_body())),
-            Some(_) => {}
+                .map_into_right_body()));
         }
 
-        // Return success
-        Either::left(AuthenticationFuture {
-            fut: self.service.call(req),
-            _phantom: PhantomData
+        // Return success if database doesn't need to be pinged
+        if !self.options.is_ping {
+            let fut = self.service.call(req);
+
+            return Box::pin(async move {
+                Ok(fut.await?.map_into_left_body())
+            });
+        }
+
+        // Check cached ping
+        if let Some(is_up) = self.options.get_cached_ping() {
+            if !is_up {
+                return Box::pin(ok(req
+                    .into_response(Payload::service_unavailable(Message::new("server.database_unavailable")).error_response())
+                    .map_into_boxed_body()
+                    .map_into_right_body()));
+            }
+
+            let fut = self.service.call(req);
+
+            return Box::pin(async move {
+                Ok(fut.await?.map_into_left_body())
+            });
+        }
+
+        // Ping database before handing over the request
+        let service = self.service.clone();
+        let options = self.options.clone();
+
+        Box::pin(async move {
+            if !options.refresh_ping(&database).await {
+                return Ok(req
+                    .into_response(Payload::service_unavailable(Message::new("server.database_unavailable")).error_response())
+                    .map_into_boxed_body()
+                    .map_into_right_body());
+            }
+
+            Ok(service.call(req).await?.map_into_left_body())
         })
     }
 }
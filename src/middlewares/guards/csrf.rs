@@ -0,0 +1,215 @@
+use actix_web::Error;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::Payload;
+
+/// Csrf guard protects cookie authenticated routes using the double-submit cookie pattern.
+/// Safe methods (GET, HEAD, OPTIONS, TRACE) pass through and receive a token cookie if missing,
+/// every other method must echo the cookie value in the csrf header or gets a 403
+#[derive(Debug, Clone)]
+pub struct CsrfGuard {
+    pub cookie_name: String,
+    pub header_name: String,
+    pub cookie_path: String,
+    pub is_secure: bool,
+    pub is_bearer_exempt: bool,
+    pub exempt_paths: Vec<String>,
+}
+
+impl Default for CsrfGuard {
+    fn default() -> Self {
+        Self {
+            cookie_name: String::from("csrf_token"),
+            header_name: String::from("X-CSRF-Token"),
+            cookie_path: String::from("/"),
+            is_secure: true,
+            is_bearer_exempt: true,
+            exempt_paths: Vec::new(),
+        }
+    }
+}
+
+impl CsrfGuard {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn set_cookie_name<T>(&self, cookie_name: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.cookie_name = cookie_name.to_string();
+        data
+    }
+
+    pub fn set_header_name<T>(&self, header_name: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.header_name = header_name.to_string();
+        data
+    }
+
+    pub fn set_cookie_path<T>(&self, cookie_path: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.cookie_path = cookie_path.to_string();
+        data
+    }
+
+    pub fn set_secure(&self, is_secure: bool) -> Self {
+        let mut data = self.clone();
+        data.is_secure = is_secure;
+        data
+    }
+
+    /// Requests carrying an `Authorization: Bearer` header and no session cookie are not
+    /// exposed to csrf, so they are skipped unless this is turned off
+    pub fn set_bearer_exempt(&self, is_bearer_exempt: bool) -> Self {
+        let mut data = self.clone();
+        data.is_bearer_exempt = is_bearer_exempt;
+        data
+    }
+
+    pub fn set_exempt_paths<T>(&self, exempt_paths: &[T]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.exempt_paths = exempt_paths.iter().map(|p| p.to_string()).collect();
+        data
+    }
+
+    // Checks if method doesn't change state
+    fn is_safe_method(method: &Method) -> bool {
+        matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+    }
+
+    // Compare tokens without leaking the matching prefix length through timing
+    fn is_token_equal(a: &str, b: &str) -> bool {
+        if a.len() != b.len() {
+            return false;
+        }
+
+        a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfGuard
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = CsrfGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CsrfGuardMiddleware {
+            service,
+            options: Rc::new(self.clone()),
+        })
+    }
+}
+
+pub struct CsrfGuardMiddleware<S> {
+    pub service: S,
+    pub options: Rc<CsrfGuard>,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfGuardMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let options = self.options.clone();
+
+        // Retrieve csrf cookie
+        let cookie = req
+            .cookie(&options.cookie_name)
+            .map(|c| c.value().to_string())
+            .filter(|c| !c.is_empty());
+
+        // Safe methods only need a token issued
+        if CsrfGuard::is_safe_method(req.method()) {
+            let fut = self.service.call(req);
+
+            return Box::pin(async move {
+                let mut res = fut.await?;
+
+                if cookie.is_none() {
+                    let cookie = Cookie::build(options.cookie_name.clone(), crate::ciphers::generate())
+                        .path(options.cookie_path.clone())
+                        .secure(options.is_secure)
+                        .http_only(false)
+                        .same_site(SameSite::Strict)
+                        .finish();
+
+                    res.response_mut().add_cookie(&cookie)?;
+                }
+
+                Ok(res.map_into_left_body())
+            });
+        }
+
+        // Check exempted paths
+        let is_exempt_path = options.exempt_paths.iter().any(|p| req.path().starts_with(p.as_str()));
+
+        // Check bearer authenticated request without cookies
+        let is_bearer = req
+            .headers()
+            .get("Authorization")
+            .map(|h| h.to_str().unwrap_or("").trim().starts_with("Bearer"))
+            .unwrap_or(false);
+        let has_cookies = req.headers().contains_key("Cookie");
+
+        if is_exempt_path || (options.is_bearer_exempt && is_bearer && !has_cookies) {
+            let fut = self.service.call(req);
+
+            return Box::pin(async move {
+                Ok(fut.await?.map_into_left_body())
+            });
+        }
+
+        // Retrieve submitted token
+        let header = req
+            .headers()
+            .get(options.header_name.as_str())
+            .map(|h| h.to_str().unwrap_or("").trim().to_string())
+            .unwrap_or_default();
+
+        let is_valid = match cookie {
+            Some(cookie) => !header.is_empty() && CsrfGuard::is_token_equal(&cookie, &header),
+            None => false
+        };
+
+        if !is_valid {
+            return Box::pin(ok(req
+                .into_response(Payload::csrf())
+                .map_into_boxed_body()
+                .map_into_right_body()));
+        }
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            Ok(fut.await?.map_into_left_body())
+        })
+    }
+}
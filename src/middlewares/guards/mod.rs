@@ -1,9 +1,11 @@
 pub mod authentication;
 pub mod cores;
+pub mod csrf;
 pub mod permissions;
 
 pub use authentication::AuthenticationFuture;
 pub use cores::CoreGuard;
+pub use csrf::CsrfGuard;
 pub use permissions::PermissionGuard;
 pub use permissions::PermissionGuardOptions;
 pub use permissions::PermissionGuardParams;
\ No newline at end of file
@@ -0,0 +1,109 @@
+use std::fmt;
+
+// Create content security policy builder
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>
+}
+
+impl ContentSecurityPolicy {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    /// Adds sources to a directive, creating the directive if it doesn't exist yet
+    pub fn directive<N, S>(&mut self, name: N, sources: &[S]) -> &mut Self
+        where N: ToString,
+              S: ToString
+    {
+        let name = name.to_string().trim().to_lowercase();
+        let sources = sources.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, values)) => for source in sources {
+                if !values.contains(&source) {
+                    values.push(source);
+                }
+            },
+            None => self.directives.push((name, sources))
+        }
+
+        self
+    }
+
+    pub fn default_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("default-src", sources)
+    }
+
+    pub fn script_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("script-src", sources)
+    }
+
+    pub fn style_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("style-src", sources)
+    }
+
+    pub fn img_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("img-src", sources)
+    }
+
+    pub fn connect_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("connect-src", sources)
+    }
+
+    pub fn font_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("font-src", sources)
+    }
+
+    pub fn media_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("media-src", sources)
+    }
+
+    pub fn object_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("object-src", sources)
+    }
+
+    pub fn frame_src<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("frame-src", sources)
+    }
+
+    pub fn frame_ancestors<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("frame-ancestors", sources)
+    }
+
+    pub fn base_uri<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("base-uri", sources)
+    }
+
+    pub fn form_action<S: ToString>(&mut self, sources: &[S]) -> &mut Self {
+        self.directive("form-action", sources)
+    }
+
+    pub fn report_uri<T: ToString>(&mut self, uri: T) -> &mut Self {
+        self.directive("report-uri", &[uri])
+    }
+
+    pub fn upgrade_insecure_requests(&mut self) -> &mut Self {
+        self.directive("upgrade-insecure-requests", &Vec::<String>::new())
+    }
+
+    pub fn build(&self) -> String {
+        self.to_string()
+    }
+}
+
+// Implement display for content security policy (header value format)
+impl fmt::Display for ContentSecurityPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.directives
+            .iter()
+            .map(|(name, sources)| match sources.is_empty() {
+                true => name.clone(),
+                false => format!("{} {}", name, sources.join(" "))
+            })
+            .collect::<Vec<String>>()
+            .join("; ");
+
+        write!(f, "{value}")
+    }
+}
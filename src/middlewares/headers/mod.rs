@@ -0,0 +1,182 @@
+pub mod csp;
+
+pub use csp::ContentSecurityPolicy;
+
+use actix_web::Error;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+/// Security headers adds HSTS, CSP, X-Frame-Options, Referrer-Policy, Permissions-Policy
+/// and X-Content-Type-Options to every response. Headers already present on the response
+/// are kept unless `set_override(true)` is used, so wrapping a route or scope with its own
+/// `SecurityHeaders` overrides the app wide defaults. Empty values disable a header
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    pub hsts: String,
+    pub content_security_policy: String,
+    pub frame_options: String,
+    pub referrer_policy: String,
+    pub permissions_policy: String,
+    pub content_type_options: String,
+    pub is_override: bool,
+}
+
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        let content_security_policy = ContentSecurityPolicy::builder()
+            .default_src(&["'self'"])
+            .object_src(&["'none'"])
+            .base_uri(&["'self'"])
+            .frame_ancestors(&["'none'"])
+            .build();
+
+        Self {
+            hsts: String::from("max-age=31536000; includeSubDomains"),
+            content_security_policy,
+            frame_options: String::from("DENY"),
+            referrer_policy: String::from("strict-origin-when-cross-origin"),
+            permissions_policy: String::from("camera=(), microphone=(), geolocation=(), payment=()"),
+            content_type_options: String::from("nosniff"),
+            is_override: false,
+        }
+    }
+}
+
+impl SecurityHeaders {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn set_hsts<T>(&self, hsts: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.hsts = hsts.to_string();
+        data
+    }
+
+    pub fn set_content_security_policy<T>(&self, content_security_policy: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.content_security_policy = content_security_policy.to_string();
+        data
+    }
+
+    pub fn set_frame_options<T>(&self, frame_options: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.frame_options = frame_options.to_string();
+        data
+    }
+
+    pub fn set_referrer_policy<T>(&self, referrer_policy: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.referrer_policy = referrer_policy.to_string();
+        data
+    }
+
+    pub fn set_permissions_policy<T>(&self, permissions_policy: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.permissions_policy = permissions_policy.to_string();
+        data
+    }
+
+    pub fn set_content_type_options<T>(&self, content_type_options: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.content_type_options = content_type_options.to_string();
+        data
+    }
+
+    pub fn set_override(&self, is_override: bool) -> Self {
+        let mut data = self.clone();
+        data.is_override = is_override;
+        data
+    }
+
+    // Returns configured headers skipping disabled (empty) ones
+    fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        vec![
+            ("strict-transport-security", &self.hsts),
+            ("content-security-policy", &self.content_security_policy),
+            ("x-frame-options", &self.frame_options),
+            ("referrer-policy", &self.referrer_policy),
+            ("permissions-policy", &self.permissions_policy),
+            ("x-content-type-options", &self.content_type_options),
+        ]
+            .into_iter()
+            .filter(|(_, value)| !value.trim().is_empty())
+            .filter_map(|(name, value)| HeaderValue::from_str(value.trim())
+                .ok()
+                .map(|value| (HeaderName::from_static(name), value)))
+            .collect()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SecurityHeaders
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SecurityHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(SecurityHeadersMiddleware {
+            service,
+            headers: Rc::new(self.headers()),
+            is_override: self.is_override,
+        })
+    }
+}
+
+// Create security headers middleware service struct
+pub struct SecurityHeadersMiddleware<S> {
+    service: S,
+    headers: Rc<Vec<(HeaderName, HeaderValue)>>,
+    is_override: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let headers = self.headers.clone();
+        let is_override = self.is_override;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            for (name, value) in headers.iter() {
+                if is_override || !res.headers().contains_key(name) {
+                    res.headers_mut().insert(name.clone(), value.clone());
+                }
+            }
+
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use actix_web::{web, Error, HttpMessage};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use chrono::{Local, Utc};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use serde::Serialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::middlewares::proxies::TrustedProxies;
+use crate::middlewares::requests::RequestId;
+use crate::middlewares::useragents::UserAgent;
+
+// Create access log entry, written as a single json line per request
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessLog {
+    pub request_id: String,
+    pub timestamp: String,
+    pub method: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    pub status: u16,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    pub user_agent: UserAgent,
+}
+
+impl AccessLog {
+    // Appends entry to today's log file in the given directory (logs.YYYY-MM-DD)
+    pub fn write<T: AsRef<Path>>(&self, directory: T) -> std::io::Result<()> {
+        let directory = directory.as_ref();
+        fs::create_dir_all(directory)?;
+
+        let filename = directory.join(format!("logs.{}", Local::now().format("%Y-%m-%d")));
+        let line = format!("{}\n", serde_json::to_string(self).unwrap_or_default());
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(filename)?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// Access logger records method, path, status, latency, ip and user agent of every request
+/// as structured json lines. Files are named `logs.YYYY-MM-DD` so `Scheduler` can prune them.
+/// Wrap it after `UserAgentParser` so the parsed user agent is available when logging
+#[derive(Debug, Clone)]
+pub struct AccessLogger {
+    pub directory: String,
+    pub show_logs: bool,
+}
+
+impl Default for AccessLogger {
+    fn default() -> Self {
+        Self {
+            directory: String::from("./logs"),
+            show_logs: false,
+        }
+    }
+}
+
+impl AccessLogger {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn set_directory<T>(&self, directory: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.directory = directory.to_string();
+        data
+    }
+
+    pub fn set_show_logs(&self, show_logs: bool) -> Self {
+        let mut data = self.clone();
+        data.show_logs = show_logs;
+        data
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLogger
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AccessLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AccessLoggerMiddleware {
+            service,
+            options: Rc::new(self.clone()),
+        })
+    }
+}
+
+// Create access logger middleware service struct
+pub struct AccessLoggerMiddleware<S> {
+    service: S,
+    options: Rc<AccessLogger>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLoggerMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let started = Instant::now();
+        let options = self.options.clone();
+
+        // Retrieve or generate request id
+        let request_id = RequestId::from_service_request(&req);
+        req.extensions_mut().insert(request_id.clone());
+
+        // Retrieve request details before handing it over
+        let method = req.method().to_string();
+        let path = req.path().to_string();
+        let query = Some(req.query_string().to_string()).filter(|q| !q.is_empty());
+        let ip = TrustedProxies::resolve(req.request());
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            // Echo request id back to the client
+            if let Some((name, value)) = request_id.to_header() {
+                res.headers_mut().insert(name, value);
+            }
+
+            // Retrieve user agent parsed by UserAgentParser if registered
+            let user_agent = res.request()
+                .extensions()
+                .get::<UserAgent>()
+                .cloned()
+                .unwrap_or_default();
+
+            let log = AccessLog {
+                request_id: request_id.to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+                method,
+                path,
+                query,
+                status: res.status().as_u16(),
+                latency_ms: started.elapsed().as_secs_f64() * 1000.0,
+                ip: user_agent.ip.clone().or(ip),
+                user_agent,
+            };
+
+            if options.show_logs {
+                println!("{}", serde_json::to_string(&log).unwrap_or_default());
+            }
+
+            // File writes run on the blocking pool so the worker isn't held up
+            let directory = options.directory.clone();
+            actix_web::rt::spawn(async move {
+                match web::block(move || log.write(directory)).await {
+                    Ok(Err(error)) => println!("Unable to write access log: {error}"),
+                    Err(error) => println!("Unable to write access log: {error}"),
+                    _ => {}
+                }
+            });
+
+            Ok(res)
+        })
+    }
+}
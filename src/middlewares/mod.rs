@@ -1,10 +1,32 @@
 pub mod guards;
+pub mod headers;
+pub mod loggers;
+pub mod problems;
+pub mod proxies;
+pub mod requests;
 pub mod useragents;
 
 pub use crate::middlewares::guards::CoreGuard;
+pub use crate::middlewares::guards::CsrfGuard;
 pub use crate::middlewares::guards::PermissionGuard;
 pub use crate::middlewares::guards::PermissionGuardOptions;
 pub use crate::middlewares::guards::PermissionGuardParams;
 
+pub use crate::middlewares::headers::ContentSecurityPolicy;
+pub use crate::middlewares::headers::SecurityHeaders;
+
+pub use crate::middlewares::loggers::AccessLog;
+pub use crate::middlewares::loggers::AccessLogger;
+
+pub use crate::middlewares::problems::ProblemNegotiator;
+
+pub use crate::middlewares::proxies::Cidr;
+pub use crate::middlewares::proxies::ForwardedHeader;
+pub use crate::middlewares::proxies::TrustedProxies;
+
+pub use crate::middlewares::requests::RequestId;
+pub use crate::middlewares::requests::RequestTracker;
+
+pub use crate::middlewares::useragents::classification::UserAgentClassification;
 pub use crate::middlewares::useragents::UserAgent;
 pub use crate::middlewares::useragents::UserAgentParser;
\ No newline at end of file
@@ -0,0 +1,74 @@
+use actix_web::Error;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::locales::Locale;
+use crate::Payload;
+
+/// Problem negotiator rewrites errors returned as `Err(Payload)` into problem details when
+/// `Payload::set_problem_mode` is `Negotiate` or `Always`, and translates them when
+/// `Data<Locales>` is registered. `ResponseError` has no access to the request so this is
+/// where `Accept`/`Accept-Language` are checked and `instance` is filled in
+#[derive(Debug, Default, Clone)]
+pub struct ProblemNegotiator;
+
+impl<S, B> Transform<S, ServiceRequest> for ProblemNegotiator
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+        S::Future: 'static,
+        B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ProblemNegotiatorMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ProblemNegotiatorMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+// Create problem negotiator middleware service struct
+pub struct ProblemNegotiatorMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ProblemNegotiatorMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+        S::Future: 'static,
+        B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            // Retrieve payload error if handler returned one
+            let response = res.response()
+                .error()
+                .and_then(|error| error.as_error::<Payload>())
+                .filter(|payload| payload.is_problem(Some(res.request())) || Locale::resolve(res.request()).is_some())
+                .map(|payload| payload.respond(res.request()));
+
+            match response {
+                Some(response) => Ok(res
+                    .into_response(response)
+                    .map_into_right_body()),
+                None => Ok(res.map_into_left_body())
+            }
+        })
+    }
+}
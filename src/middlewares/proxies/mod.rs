@@ -0,0 +1,232 @@
+use actix_web::{HttpRequest, Result};
+use actix_web::web::Data;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use crate::Payload;
+
+// Create cidr block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cidr {
+    pub network: IpAddr,
+    pub prefix: u8,
+}
+
+impl Cidr {
+    // Checks if ip belongs to this block
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        match (self.network, normalize(*ip)) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                (u32::from(network) & mask) == (u32::from(ip) & mask)
+            },
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                (u128::from(network) & mask) == (u128::from(ip) & mask)
+            },
+            _ => false
+        }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = String;
+
+    // Parses `10.0.0.0/8`, `2001:db8::/32` or a single address
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let value = value.trim();
+        let (network, prefix) = match value.split_once('/') {
+            Some((network, prefix)) => (network, Some(prefix)),
+            None => (value, None)
+        };
+
+        let network = IpAddr::from_str(network)
+            .map(normalize)
+            .map_err(|_| format!("Invalid proxy address: {value}"))?;
+
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128
+        };
+
+        let prefix = match prefix {
+            Some(prefix) => match prefix.parse::<u8>() {
+                Ok(prefix) if prefix <= max => prefix,
+                _ => return Err(format!("Invalid proxy prefix: {value}"))
+            },
+            None => max
+        };
+
+        Ok(Self { network, prefix })
+    }
+}
+
+// Create forwarding header written by the trusted proxies
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ForwardedHeader {
+    #[default]
+    XForwardedFor,
+    Forwarded,
+    XRealIp,
+}
+
+impl ForwardedHeader {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::XForwardedFor => "X-Forwarded-For",
+            Self::Forwarded => "Forwarded",
+            Self::XRealIp => "X-Real-IP",
+        }
+    }
+}
+
+/// Trusted proxies resolves the client ip from the forwarding header set with `set_header`
+/// (`X-Forwarded-For` by default), only believing hops sent by a trusted proxy. Other forwarding
+/// headers are ignored since clients can send them too. Register it as app data
+/// (`Data<TrustedProxies>`) so `UserAgentParser` and `AccessLogger` use it. Without it only the
+/// peer address is used
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TrustedProxies {
+    pub cidrs: Vec<Cidr>,
+    pub header: ForwardedHeader,
+}
+
+impl TrustedProxies {
+    pub fn new<T>(cidrs: &[T]) -> Result<Self>
+        where T: ToString
+    {
+        let mut data = Self::default();
+
+        for cidr in cidrs {
+            match Cidr::from_str(&cidr.to_string()) {
+                Ok(cidr) => data.cidrs.push(cidr),
+                Err(error) => return Err(Payload::error(error))
+            }
+        }
+
+        Ok(data)
+    }
+
+    // Trusts loopback and private network ranges, common for apps behind a local load balancer
+    pub fn private() -> Self {
+        let cidrs = ["127.0.0.0/8", "10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "::1/128", "fc00::/7"]
+            .iter()
+            .filter_map(|cidr| Cidr::from_str(cidr).ok())
+            .collect();
+
+        Self { cidrs, ..Default::default() }
+    }
+
+    // Sets the only header read for forwarded hops, match it to what the proxy writes
+    pub fn set_header(&self, header: ForwardedHeader) -> Self {
+        let mut data = self.clone();
+        data.header = header;
+        data
+    }
+
+    pub fn is_trusted(&self, ip: &IpAddr) -> bool {
+        self.cidrs.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// Returns the first untrusted hop walking from the peer address towards the client
+    pub fn client_ip(&self, req: &HttpRequest) -> Option<IpAddr> {
+        let peer = req.peer_addr().map(|addr| normalize(addr.ip()))?;
+        if !self.is_trusted(&peer) {
+            return Some(peer);
+        }
+
+        // Retrieve forwarded chain, left most is the original client
+        let chain = forwarded_chain(req, self.header);
+        let mut client = peer;
+
+        for hop in chain.iter().rev() {
+            match hop {
+                Some(ip) => {
+                    client = *ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                },
+                None => break
+            }
+        }
+
+        Some(client)
+    }
+
+    /// Resolves client ip using the registered `Data<TrustedProxies>`, or the peer address
+    pub fn resolve(req: &HttpRequest) -> Option<String> {
+        let ip = match req.app_data::<Data<TrustedProxies>>() {
+            Some(proxies) => proxies.client_ip(req),
+            None => req.peer_addr().map(|addr| normalize(addr.ip()))
+        };
+
+        ip.map(|ip| ip.to_string())
+    }
+}
+
+// Maps ipv4 mapped ipv6 addresses (::ffff:a.b.c.d) back to ipv4
+fn normalize(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => IpAddr::V6(v6)
+        },
+        IpAddr::V4(_) => ip
+    }
+}
+
+// Parses a forwarded hop such as `192.0.2.1`, `192.0.2.1:80`, `"[2001:db8::1]:4711"` or `2001:db8::1`
+fn parse_hop(value: &str) -> Option<IpAddr> {
+    let value = value.trim().trim_matches('"');
+
+    if let Ok(ip) = IpAddr::from_str(value) {
+        return Some(normalize(ip));
+    }
+
+    if let Some(rest) = value.strip_prefix('[') {
+        return rest
+            .split(']')
+            .next()
+            .and_then(|ip| IpAddr::from_str(ip).ok())
+            .map(normalize);
+    }
+
+    value
+        .rsplit_once(':')
+        .and_then(|(ip, _)| IpAddr::from_str(ip).ok())
+        .map(normalize)
+}
+
+// Retrieves hops from the given header only, unparsable hops are kept as None
+fn forwarded_chain(req: &HttpRequest, header: ForwardedHeader) -> Vec<Option<IpAddr>> {
+    let values = req
+        .headers()
+        .get_all(header.as_str())
+        .filter_map(|h| h.to_str().ok());
+
+    match header {
+        ForwardedHeader::Forwarded => values
+            .flat_map(|h| h.split(','))
+            .map(|element| element
+                .split(';')
+                .find_map(|pair| pair
+                    .trim()
+                    .split_once('=')
+                    .filter(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                    .map(|(_, value)| parse_hop(value)))
+                .flatten())
+            .collect(),
+        ForwardedHeader::XForwardedFor => values
+            .flat_map(|h| h.split(','))
+            .filter(|hop| !hop.trim().is_empty())
+            .map(parse_hop)
+            .collect(),
+        // Set by the proxy as a single address, repeated headers are ambiguous
+        ForwardedHeader::XRealIp => match values.collect::<Vec<&str>>().as_slice() {
+            [value] => vec![parse_hop(value)],
+            [] => Vec::new(),
+            _ => vec![None]
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use rand::Rng;
+use serde::Serialize;
+
+/// Header used to receive and propagate request ids
+pub static REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+// Create request id object
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RequestId(pub String);
+
+// Create implementation for request id
+impl RequestId {
+    // Generates a new random request id (uuid v4 format)
+    pub fn new() -> Self {
+        let mut bytes = rand::thread_rng().gen::<[u8; 16]>();
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let hex = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+
+        Self(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+    }
+
+    // Retrieves the request id sent by the client or a proxy, generating one if missing or malformed
+    pub fn from_service_request(req: &ServiceRequest) -> Self {
+        if let Some(request_id) = req.extensions().get::<RequestId>() {
+            return request_id.clone();
+        }
+
+        req.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.trim())
+            .filter(|h| Self::is_valid(h))
+            .map(|h| Self(h.to_string()))
+            .unwrap_or_default()
+    }
+
+    // Only accept short visible ascii ids so they can't be used to inject into logs
+    fn is_valid(value: &str) -> bool {
+        !value.is_empty()
+            && value.len() <= 128
+            && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+    }
+
+    // Converts request id to header value
+    pub fn to_header_value(&self) -> Option<HeaderValue> {
+        HeaderValue::from_str(&self.0).ok()
+    }
+
+    // Converts request id to the `REQUEST_ID_HEADER` name and value pair
+    pub fn to_header(&self) -> Option<(HeaderName, HeaderValue)> {
+        let name = HeaderName::from_bytes(REQUEST_ID_HEADER.as_bytes()).ok()?;
+        Some((name, self.to_header_value()?))
+    }
+}
+
+// Implement default for request id
+impl Default for RequestId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implement display for request id
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Implement from request
+impl FromRequest for RequestId {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        match req.extensions().get::<RequestId>() {
+            Some(request_id) => ok(request_id.clone()),
+            None => ok(RequestId::new())
+        }
+    }
+}
+
+/// Request tracker assigns an `X-Request-Id` to every request (or propagates the one received)
+/// and echoes it back on the response so logs can be correlated across services
+pub struct RequestTracker;
+
+// Create implementation for request tracker
+impl RequestTracker {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+// Implement default for request tracker
+impl Default for RequestTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTracker
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTrackerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTrackerMiddleware { service })
+    }
+}
+
+// Create request tracker middleware service struct
+pub struct RequestTrackerMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTrackerMiddleware<S>
+    where
+        S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+        S::Future: 'static,
+        B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        // Retrieve or generate request id
+        let request_id = RequestId::from_service_request(&req);
+        req.extensions_mut().insert(request_id.clone());
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            // Echo request id back to the client
+            if let Some((name, value)) = request_id.to_header() {
+                res.headers_mut().insert(name, value);
+            }
+
+            Ok(res)
+        })
+    }
+}
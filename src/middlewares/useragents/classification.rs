@@ -0,0 +1,158 @@
+use regex::Regex;
+// Bring serde crate to scope
+use serde::Serialize;
+use std::fmt;
+use std::sync::OnceLock;
+
+// Known crawlers as (user agent token, name)
+pub static KNOWN_CRAWLERS: &[(&str, &str)] = &[
+    ("googlebot", "Googlebot"),
+    ("google-inspectiontool", "Google Inspection Tool"),
+    ("adsbot-google", "AdsBot Google"),
+    ("mediapartners-google", "Google AdSense"),
+    ("bingbot", "Bingbot"),
+    ("bingpreview", "Bing Preview"),
+    ("slurp", "Yahoo Slurp"),
+    ("duckduckbot", "DuckDuckBot"),
+    ("baiduspider", "Baiduspider"),
+    ("yandexbot", "YandexBot"),
+    ("sogou", "Sogou Spider"),
+    ("exabot", "Exabot"),
+    ("applebot", "Applebot"),
+    ("amazonbot", "Amazonbot"),
+    ("petalbot", "PetalBot"),
+    ("bytespider", "Bytespider"),
+    ("facebookexternalhit", "Facebook External Hit"),
+    ("facebot", "Facebot"),
+    ("twitterbot", "Twitterbot"),
+    ("linkedinbot", "LinkedInBot"),
+    ("pinterestbot", "Pinterestbot"),
+    ("redditbot", "Redditbot"),
+    ("slackbot", "Slackbot"),
+    ("discordbot", "Discordbot"),
+    ("telegrambot", "TelegramBot"),
+    ("whatsapp", "WhatsApp"),
+    ("skypeuripreview", "Skype URI Preview"),
+    ("ahrefsbot", "AhrefsBot"),
+    ("semrushbot", "SemrushBot"),
+    ("mj12bot", "MJ12bot"),
+    ("dotbot", "DotBot"),
+    ("gptbot", "GPTBot"),
+    ("ccbot", "CCBot"),
+    ("uptimerobot", "UptimeRobot"),
+    ("pingdom", "Pingdom"),
+];
+
+// Tokens used by http libraries and tools
+static BOT_TOKENS: &[&str] = &[
+    "curl/", "wget/", "python-requests", "python-urllib", "aiohttp", "go-http-client", "java/",
+    "apache-httpclient", "libwww-perl", "httpie", "postmanruntime", "insomnia", "axios/", "node-fetch",
+];
+
+// Generic bot words only count on product token boundaries so names like "CUBOT" don't match.
+// Matches a standalone word (`bot;`, `-bot`), a versioned product (`somebot/1.0`) or a camel cased
+// suffix (`SomeBot`, `FooSpider`)
+static BOT_PATTERN: &str = r"(?i:(?:^|[\s;(,+_-])(?:bot|crawler|spider|scraper)(?:$|[\s;)/,+_-])|[a-z0-9](?:bot|crawler|spider)/\d)|[a-z0-9](?:Bot|Crawler|Spider)\b";
+
+// Compiled bot pattern
+static BOT_REGEX: OnceLock<Regex> = OnceLock::new();
+
+// Tokens used by headless and automated browsers
+static HEADLESS_TOKENS: &[&str] = &["headlesschrome", "phantomjs", "puppeteer", "playwright", "selenium"];
+
+// Tokens used by in-app browsers
+static WEBVIEW_TOKENS: &[&str] = &["; wv)", "webview", "fban/", "fbav/", "instagram", "line/", "micromessenger", "gsa/"];
+
+// User agent classification
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum UserAgentClassification {
+    Bot,
+    HeadlessBrowser,
+    Webview,
+    MobileBrowser,
+    DesktopBrowser,
+    #[default]
+    Unknown,
+}
+
+impl UserAgentClassification {
+    /// Classifies raw user agent string along with parsed os, device and product names
+    pub fn classify(raw: &str, os: Option<&str>, device: Option<&str>, product: Option<&str>) -> Self {
+        let lower = raw.trim().to_lowercase();
+        if lower.is_empty() {
+            return Self::Unknown;
+        }
+
+        // Check for crawlers and automated clients
+        if crawler(&lower).is_some()
+            || device.unwrap_or_default() == "Spider"
+            || BOT_TOKENS.iter().any(|token| lower.contains(token))
+            || is_bot_token(raw) {
+            return Self::Bot;
+        }
+
+        // Check for headless browsers
+        if HEADLESS_TOKENS.iter().any(|token| lower.contains(token))
+            || product.unwrap_or_default().to_lowercase().contains("headless") {
+            return Self::HeadlessBrowser;
+        }
+
+        // Check for webviews (android marks them with `wv`, ios webviews don't report Safari)
+        let os = os.unwrap_or_default().to_lowercase();
+        let is_ios = os == "ios" || lower.contains("iphone") || lower.contains("ipad");
+        if WEBVIEW_TOKENS.iter().any(|token| lower.contains(token))
+            || (is_ios && lower.contains("applewebkit") && !lower.contains("safari/")) {
+            return Self::Webview;
+        }
+
+        // Check for mobile browsers
+        if is_ios || os == "android" || lower.contains("mobile") || lower.contains("android") {
+            return Self::MobileBrowser;
+        }
+
+        // Check for desktop browsers
+        match product.filter(|p| !p.is_empty() && *p != "Other") {
+            Some(_) => Self::DesktopBrowser,
+            None => Self::Unknown
+        }
+    }
+
+    pub fn is_bot(&self) -> bool {
+        matches!(self, Self::Bot | Self::HeadlessBrowser)
+    }
+
+    pub fn is_mobile(&self) -> bool {
+        matches!(self, Self::Webview | Self::MobileBrowser)
+    }
+}
+
+// Implement display for user agent classification
+impl fmt::Display for UserAgentClassification {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bot => write!(f, "Bot"),
+            Self::HeadlessBrowser => write!(f, "HeadlessBrowser"),
+            Self::Webview => write!(f, "Webview"),
+            Self::MobileBrowser => write!(f, "MobileBrowser"),
+            Self::DesktopBrowser => write!(f, "DesktopBrowser"),
+            Self::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+// Checks for generic bot words on product token boundaries
+fn is_bot_token(raw: &str) -> bool {
+    BOT_REGEX
+        .get_or_init(|| Regex::new(BOT_PATTERN).expect("valid bot pattern"))
+        .is_match(raw)
+}
+
+/// Returns the name of a known crawler found in the user agent string
+pub fn crawler(raw: &str) -> Option<&'static str> {
+    let lower = raw.to_lowercase();
+
+    KNOWN_CRAWLERS
+        .iter()
+        .find(|(token, _)| lower.contains(token))
+        .map(|(_, name)| *name)
+}
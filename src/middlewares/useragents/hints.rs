@@ -0,0 +1,205 @@
+use actix_web::http::header::HeaderMap;
+use serde::Serialize;
+
+use crate::middlewares::useragents::UserAgent;
+
+/// High entropy hints requested through `Accept-CH`, low entropy ones are always sent
+pub static ACCEPT_CH: &str = "Sec-CH-UA-Full-Version-List, Sec-CH-UA-Platform-Version, Sec-CH-UA-Model, Sec-CH-UA-Arch, Sec-CH-UA-Bitness";
+
+// Insert user agent client hint brand
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UserAgentBrand {
+    pub brand: String,
+    pub version: String,
+}
+
+// Insert user agent client hints
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct UserAgentHints {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub brands: Vec<UserAgentBrand>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub full_version_list: Vec<UserAgentBrand>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mobile: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bitness: Option<String>,
+}
+
+// Create implementation for user agent hints
+impl UserAgentHints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Parses Sec-CH-UA-* request headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let get = |name: &str| headers
+            .get(name)
+            .and_then(|h| h.to_str().ok())
+            .map(|h| h.trim().to_string())
+            .filter(|h| !h.is_empty());
+
+        let string = |name: &str| get(name)
+            .map(|h| h.trim_matches('"').to_string())
+            .filter(|h| !h.is_empty());
+
+        Self {
+            brands: get("Sec-CH-UA").map(|h| parse_brands(&h)).unwrap_or_default(),
+            full_version_list: get("Sec-CH-UA-Full-Version-List").map(|h| parse_brands(&h)).unwrap_or_default(),
+            platform: string("Sec-CH-UA-Platform"),
+            platform_version: string("Sec-CH-UA-Platform-Version"),
+            mobile: get("Sec-CH-UA-Mobile").and_then(|h| match h.as_str() {
+                "?1" => Some(true),
+                "?0" => Some(false),
+                _ => None
+            }),
+            model: string("Sec-CH-UA-Model"),
+            arch: string("Sec-CH-UA-Arch"),
+            bitness: string("Sec-CH-UA-Bitness"),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // Returns the brand that identifies the browser, skipping GREASE brands and preferring
+    // the actual product over the Chromium engine
+    pub fn get_brand(&self) -> Option<UserAgentBrand> {
+        let brands = match self.full_version_list.is_empty() {
+            true => &self.brands,
+            false => &self.full_version_list
+        };
+
+        let brands = brands
+            .iter()
+            .filter(|b| !is_grease(&b.brand))
+            .collect::<Vec<&UserAgentBrand>>();
+
+        brands
+            .iter()
+            .find(|b| b.brand != "Chromium")
+            .or(brands.first())
+            .map(|b| (*b).clone())
+    }
+
+    /// Merges hints into the user agent parsed from the frozen `User-Agent` string,
+    /// hints take precedence since they aren't reduced
+    pub fn merge(&self, user_agent: &mut UserAgent) {
+        if self.is_empty() {
+            return;
+        }
+
+        // Set product
+        if let Some(brand) = self.get_brand() {
+            let (major, minor, patch) = split_version(&brand.version);
+            user_agent.product.name = Some(brand.brand);
+            user_agent.product.major = major.or(user_agent.product.major.clone());
+
+            // Reduced user agents and low entropy hints both report 0 for minor and patch
+            if minor.is_some() {
+                user_agent.product.minor = minor;
+                user_agent.product.patch = patch;
+            }
+        }
+
+        // Set os
+        if let Some(platform) = self.platform.clone() {
+            let platform = match platform.as_str() {
+                "macOS" => String::from("Mac OS X"),
+                _ => platform
+            };
+
+            if user_agent.os.name.as_deref() != Some(platform.as_str()) {
+                user_agent.os.name = Some(platform.clone());
+                user_agent.os.major = None;
+                user_agent.os.minor = None;
+                user_agent.os.patch = None;
+                user_agent.os.patch_minor = None;
+            }
+
+            if let Some(version) = self.platform_version.clone() {
+                let (major, minor, patch) = split_version(&version);
+
+                // Windows reports its platform version (13+ is Windows 11) instead of a release
+                match platform.as_str() {
+                    "Windows" => match major.and_then(|major| major.parse::<i32>().ok()) {
+                        Some(major) if major >= 13 => user_agent.os.major = Some(String::from("11")),
+                        Some(major) if major >= 1 => user_agent.os.major = Some(String::from("10")),
+                        _ => {}
+                    },
+                    _ => {
+                        user_agent.os.major = major;
+                        user_agent.os.minor = minor;
+                        user_agent.os.patch = patch;
+                    }
+                }
+            }
+        }
+
+        // Set device model
+        if let Some(model) = self.model.clone() {
+            user_agent.device.model = Some(model.clone());
+            if user_agent.device.name.is_none() || user_agent.device.name.as_deref() == Some("K") {
+                user_agent.device.name = Some(model);
+            }
+        }
+
+        // Set architecture
+        if let Some(arch) = self.arch.clone() {
+            user_agent.cpu.architecture = match (arch.as_str(), self.bitness.as_deref()) {
+                ("x86", Some("64")) => Some(String::from("amd64")),
+                ("x86", _) => Some(String::from("ia32")),
+                ("arm", Some("64")) => Some(String::from("arm64")),
+                _ => Some(arch.to_lowercase())
+            };
+        }
+
+        user_agent.hints = Some(self.clone());
+    }
+}
+
+// Checks for GREASE brands such as "Not_A Brand" or "Not/A)Brand"
+fn is_grease(brand: &str) -> bool {
+    let brand = brand.to_lowercase();
+    brand.contains("not") && brand.contains("brand")
+}
+
+// Parses structured header list `"Chromium";v="124", "Google Chrome";v="124"`
+fn parse_brands(value: &str) -> Vec<UserAgentBrand> {
+    value
+        .split(',')
+        .filter_map(|item| {
+            let mut parts = item.split(';');
+            let brand = parts.next()?.trim().trim_matches('"').to_string();
+            let version = parts
+                .find_map(|param| param.trim().strip_prefix("v="))
+                .map(|v| v.trim().trim_matches('"').to_string())
+                .unwrap_or_default();
+
+            match brand.is_empty() {
+                true => None,
+                false => Some(UserAgentBrand { brand, version })
+            }
+        })
+        .collect()
+}
+
+// Splits version into major, minor and patch
+fn split_version(version: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let mut parts = version
+        .split('.')
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+
+    (parts.next(), parts.next(), parts.next())
+}
@@ -1,28 +1,158 @@
+pub mod classification;
 pub mod cpu;
 pub mod device;
 pub mod engine;
+pub mod hints;
 pub mod os;
 pub mod product;
 
 use actix_web::{Error, FromRequest, HttpMessage, HttpRequest, web::Data};
+use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::dev::{forward_ready, Payload, Service, ServiceRequest, ServiceResponse, Transform};
-use futures::future::{ok, Ready};
+use futures::future::{ok, LocalBoxFuture, Ready};
 use serde::Serialize;
+use lru_cache::LruCache;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
 use user_agent_parser::UserAgentParser as UAParser;
 
+use crate::middlewares::proxies::TrustedProxies;
+use crate::middlewares::useragents::classification::UserAgentClassification;
 use crate::middlewares::useragents::cpu::UserAgentCPU;
 use crate::middlewares::useragents::device::UserAgentDevice;
 use crate::middlewares::useragents::engine::UserAgentEngine;
+use crate::middlewares::useragents::hints::UserAgentHints;
 use crate::middlewares::useragents::os::UserAgentOS;
 use crate::middlewares::useragents::product::UserAgentProduct;
 
-// Create user agent parser
-pub struct UserAgentParser;
+/// Bundled uap-core regexes, a reduced set. Use `UserAgentParser::from_path` for the full upstream file
+pub static USER_AGENT_REGEXES: &str = include_str!("regexes.yaml");
+
+// Bundled regexes are parsed once per process and shared by every parser
+static BUNDLED_PARSER: OnceLock<Option<Arc<UAParser>>> = OnceLock::new();
+
+// Retrieves the parser built from the bundled regexes
+fn bundled_parser() -> Option<Arc<UAParser>> {
+    BUNDLED_PARSER
+        .get_or_init(|| match UAParser::from_str(USER_AGENT_REGEXES) {
+            Ok(parser) => Some(Arc::new(parser)),
+            Err(error) => {
+                println!("Unable to load bundled user agent regexes: {error}");
+                None
+            }
+        })
+        .clone()
+}
+
+/// User agent parser middleware parses the `User-Agent` header into `UserAgent` request extension.
+/// The regexes are taken from (in order) the parser given to `from_path`/`from_yaml`/`from_parser`,
+/// a `Data<user_agent_parser::UserAgentParser>` registered as app data, or the bundled regexes.
+/// Regexes are loaded when the parser is built so errors surface there instead of on a request
+#[derive(Clone)]
+pub struct UserAgentParser {
+    pub cache_size: usize,
+    pub is_accept_ch: bool,
+    parser: Option<Arc<UAParser>>,
+    bundled: Option<Arc<UAParser>>,
+    cache: Arc<Mutex<LruCache<String, UserAgent>>>,
+}
 
 // Create implementation for user agent parser
 impl UserAgentParser {
     pub fn new() -> Self {
-        Self
+        Self {
+            cache_size: 1024,
+            is_accept_ch: false,
+            parser: None,
+            bundled: bundled_parser(),
+            cache: Arc::new(Mutex::new(LruCache::new(1024))),
+        }
+    }
+
+    // Creates parser from a regexes.yaml file e.g. the full uap-core file
+    pub fn from_path<T>(path: T) -> Result<Self, user_agent_parser::UserAgentParserError>
+        where T: AsRef<Path>
+    {
+        Ok(Self::from_parser(UAParser::from_path(path)?))
+    }
+
+    // Creates parser from yaml contents
+    pub fn from_yaml<T>(yaml: T) -> Result<Self, user_agent_parser::UserAgentParserError>
+        where T: AsRef<str>
+    {
+        Ok(Self::from_parser(UAParser::from_str(yaml)?))
+    }
+
+    // Creates parser from an already loaded parser
+    pub fn from_parser(parser: UAParser) -> Self {
+        let mut data = Self::new();
+        data.parser = Some(Arc::new(parser));
+        data
+    }
+
+    // Sets maximum number of parsed user agents kept in memory, least recently used ones are
+    // dropped first. 0 disables the cache
+    pub fn set_cache_size(&self, cache_size: usize) -> Self {
+        let mut data = self.clone();
+        data.cache_size = cache_size;
+        data.cache = Arc::new(Mutex::new(LruCache::new(cache_size.max(1))));
+        data
+    }
+
+    // Sends `Accept-CH` on responses so chromium browsers include high entropy client hints
+    pub fn set_accept_ch(&self, is_accept_ch: bool) -> Self {
+        let mut data = self.clone();
+        data.is_accept_ch = is_accept_ch;
+        data
+    }
+
+    // Retrieves parser from self, app data or the bundled regexes
+    fn get_parser(&self, req: &ServiceRequest) -> Option<Arc<UAParser>> {
+        if let Some(parser) = self.parser.as_ref() {
+            return Some(parser.clone());
+        }
+
+        if let Some(parser) = req.app_data::<Data<UAParser>>() {
+            return Some(parser.clone().into_inner());
+        }
+
+        self.bundled.clone()
+    }
+
+    // Parses user agent string (reusing previously parsed results) and merges client hints
+    pub fn parse<T: Into<String>>(&self, req: &ServiceRequest, ua_str: &str, ip: T) -> UserAgent {
+        let ip = ip.into();
+        let ip = Some(ip).filter(|ip| !ip.is_empty());
+
+        let mut user_agent = self.parse_user_agent(req, ua_str);
+        UserAgentHints::from_headers(req.headers()).merge(&mut user_agent);
+        user_agent.ip = ip;
+        user_agent
+    }
+
+    // Parses classic user agent string
+    fn parse_user_agent(&self, req: &ServiceRequest, ua_str: &str) -> UserAgent {
+        if self.cache_size > 0 {
+            if let Some(user_agent) = self.cache.lock().ok().and_then(|mut cache| cache.get_mut(ua_str).cloned()) {
+                return user_agent;
+            }
+        }
+
+        let user_agent = match self.get_parser(req) {
+            Some(parser) => UserAgent::from_parser(&parser, ua_str, ""),
+            None => return UserAgent {
+                raw: Some(ua_str.to_string()).filter(|ua| !ua.trim().is_empty()),
+                ..Default::default()
+            }
+        };
+
+        if self.cache_size > 0 {
+            if let Ok(mut cache) = self.cache.lock() {
+                cache.insert(ua_str.to_string(), user_agent.clone());
+            }
+        }
+
+        user_agent
     }
 }
 
@@ -49,13 +179,14 @@ impl<S, B> Transform<S, ServiceRequest> for UserAgentParser
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ok(UserAgentParserMiddleware { service })
+        ok(UserAgentParserMiddleware { service, parser: self.clone() })
     }
 }
 
 // Create user agent parser middleware service struct
 pub struct UserAgentParserMiddleware<S> {
     service: S,
+    parser: UserAgentParser,
 }
 
 // Implement service for middleware
@@ -67,7 +198,7 @@ impl<S, B> Service<ServiceRequest> for UserAgentParserMiddleware<S>
 {
     type Response = ServiceResponse<B>;
     type Error = Error;
-    type Future = S::Future;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     forward_ready!(service);
 
@@ -79,22 +210,29 @@ impl<S, B> Service<ServiceRequest> for UserAgentParserMiddleware<S>
             .map(|h| h.to_str().unwrap_or(""))
             .unwrap_or("");
 
-        // Retrieve request
-        let ip = req
-            .connection_info()
-            .realip_remote_addr()
-            .map_or(String::default(), |item| item.to_string());
+        // Retrieve client ip, forwarded headers are only trusted from registered proxies
+        let ip = TrustedProxies::resolve(req.request()).unwrap_or_default();
 
-        // Retrieve parser
-        let parser = req.app_data::<Data<UAParser>>()
-            .unwrap()
-            .clone();
+        // Parse user agent
+        let user_agent = self.parser.parse(&req, ua_str, ip);
 
-        // Insert user agent object
-        req.extensions_mut().insert(UserAgent::from_parser(&parser, ua_str, ip));
+        // Insert user agent object and its classification
+        req.extensions_mut().insert(user_agent.classification());
+        req.extensions_mut().insert(user_agent);
 
         // Return service call req
-        self.service.call(req)
+        let is_accept_ch = self.parser.is_accept_ch;
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+
+            if is_accept_ch {
+                res.headers_mut().insert(HeaderName::from_static("accept-ch"), HeaderValue::from_static(hints::ACCEPT_CH));
+            }
+
+            Ok(res)
+        })
     }
 }
 
@@ -103,6 +241,10 @@ impl<S, B> Service<ServiceRequest> for UserAgentParserMiddleware<S>
 #[derive(Debug, Clone, Serialize)]
 pub struct UserAgent {
     pub ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<UserAgentHints>,
     pub product: UserAgentProduct,
     pub os: UserAgentOS,
     pub device: UserAgentDevice,
@@ -116,6 +258,8 @@ impl UserAgent {
     pub fn new() -> Self {
         Self {
             ip: None,
+            raw: None,
+            hints: None,
             product: UserAgentProduct::new(),
             os: UserAgentOS::new(),
             device: UserAgentDevice::new(),
@@ -165,6 +309,11 @@ impl UserAgent {
         user_agent.engine.minor = ua_engine.minor.map(|item| item.to_string());
         user_agent.engine.patch = ua_engine.patch.map(|item| item.to_string());
 
+        // Set raw user agent string
+        if !ua_str.trim().is_empty() {
+            user_agent.raw = Some(ua_str.to_string());
+        }
+
         // Check if ip is not empty
         if !bindings.is_empty() {
             user_agent.ip = Some(bindings);
@@ -174,6 +323,27 @@ impl UserAgent {
         user_agent
     }
 
+    // Classifies user agent as bot, headless browser, webview, mobile or desktop browser
+    pub fn classification(&self) -> UserAgentClassification {
+        let classification = UserAgentClassification::classify(
+            self.raw.as_deref().unwrap_or_default(),
+            self.os.name.as_deref(),
+            self.device.name.as_deref(),
+            self.product.name.as_deref()
+        );
+
+        // Reduced user agents may hide mobile devices, trust the Sec-CH-UA-Mobile hint
+        match (classification, self.hints.as_ref().and_then(|h| h.mobile)) {
+            (UserAgentClassification::DesktopBrowser, Some(true)) => UserAgentClassification::MobileBrowser,
+            _ => classification
+        }
+    }
+
+    // Returns the name of the known crawler if the request came from one
+    pub fn crawler(&self) -> Option<&'static str> {
+        self.raw.as_deref().and_then(classification::crawler)
+    }
+
     // Convert self to json value
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::to_value(self.clone()).unwrap()
@@ -198,4 +368,17 @@ impl FromRequest for UserAgent {
             None => ok(UserAgent::new())
         };
     }
+}
+
+// Implement from request
+impl FromRequest for UserAgentClassification {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        match req.extensions().get::<UserAgentClassification>() {
+            Some(classification) => ok(*classification),
+            None => ok(UserAgentClassification::default())
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::StatusCode;
+use actix_web::web::Data;
+use handlebars::Handlebars;
+use serde::Serialize;
+use std::sync::{Arc, RwLock};
+
+use crate::traits::IsEmpty;
+use crate::CoreGuard;
+use crate::Mailer;
+use crate::MongoDBManager;
+use crate::Paseto;
+use crate::Payload;
+use crate::S3;
+
+// Component statuses
+pub static STATUS_UP: &str = "up";
+pub static STATUS_DOWN: &str = "down";
+pub static STATUS_MISSING: &str = "missing";
+pub static STATUS_UNCONFIGURED: &str = "unconfigured";
+
+// Struct container for component statuses
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Health {
+    pub status: String,
+    pub mongodb: String,
+    pub handlebars: String,
+    pub paseto: String,
+    pub mailer: String,
+    pub s3: String,
+}
+
+impl Health {
+    /// Checks every component registered as app data. Mongo is pinged with the timeout of the
+    /// `CoreGuard` registered as app data (or its default), the rest are checked for registration
+    /// and configuration. Mailer and S3 are optional and never make the report fail
+    pub async fn check(req: &HttpRequest) -> Self {
+        let core_guard = req.app_data::<CoreGuard>()
+            .cloned()
+            .or(req.app_data::<Data<CoreGuard>>().map(|guard| guard.get_ref().clone()))
+            .unwrap_or_default();
+
+        let mongodb = match req.app_data::<Data<MongoDBManager>>() {
+            None => STATUS_MISSING,
+            Some(database) => match core_guard.ping(database).await {
+                true => STATUS_UP,
+                false => STATUS_DOWN
+            }
+        };
+
+        let handlebars = match req.app_data::<Data<Handlebars<'_>>>() {
+            None => STATUS_MISSING,
+            Some(_) => STATUS_UP
+        };
+
+        let paseto = match req.app_data::<Data<Arc<RwLock<Paseto>>>>() {
+            None => STATUS_MISSING,
+            Some(paseto) => match paseto.read().map(|p| p.is_empty()).unwrap_or(true) {
+                true => STATUS_UNCONFIGURED,
+                false => STATUS_UP
+            }
+        };
+
+        let mailer = match req.app_data::<Data<Arc<RwLock<Mailer>>>>() {
+            None => STATUS_MISSING,
+            Some(mailer) => match mailer.read().map(|m| m.is_empty()).unwrap_or(true) {
+                true => STATUS_UNCONFIGURED,
+                false => STATUS_UP
+            }
+        };
+
+        let s3 = match req.app_data::<Data<Arc<RwLock<S3>>>>() {
+            None => STATUS_MISSING,
+            Some(s3) => match s3.read().map(|s| s.is_empty()).unwrap_or(true) {
+                true => STATUS_UNCONFIGURED,
+                false => STATUS_UP
+            }
+        };
+
+        let is_ready = mongodb == STATUS_UP && handlebars == STATUS_UP && paseto == STATUS_UP;
+
+        Self {
+            status: String::from(if is_ready { STATUS_UP } else { STATUS_DOWN }),
+            mongodb: mongodb.to_string(),
+            handlebars: handlebars.to_string(),
+            paseto: paseto.to_string(),
+            mailer: mailer.to_string(),
+            s3: s3.to_string(),
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.status.as_str() == STATUS_UP
+    }
+}
+
+// Builds json response for health report
+fn http_response(health: Health, status_code: StatusCode) -> HttpResponse {
+    let payload = Payload {
+        code: Some(status_code.as_u16()),
+        data: Some(serde_json::to_value(health).unwrap_or(serde_json::Value::Null)),
+        ..Default::default()
+    };
+
+    HttpResponse::build(status_code)
+        .content_type("application/json")
+        .body(serde_json::to_string(&payload).unwrap())
+}
+
+/// Liveness handler, always responds 200 while reporting component statuses.
+/// Register it outside of `CoreGuard` e.g. `web::resource("/health").route(web::get().to(monitors::health))`
+pub async fn health(req: HttpRequest) -> HttpResponse {
+    http_response(Health::check(&req).await, StatusCode::OK)
+}
+
+/// Readiness handler, responds 503 when mongo, handlebars or paseto isn't available
+pub async fn ready(req: HttpRequest) -> HttpResponse {
+    let health = Health::check(&req).await;
+
+    match health.is_ready() {
+        true => http_response(health, StatusCode::OK),
+        false => http_response(health, StatusCode::SERVICE_UNAVAILABLE)
+    }
+}
@@ -1,8 +1,16 @@
- use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
 
 // Converts string to naive date time
 pub fn naive_date_time<T>(value: T) -> Option<NaiveDateTime>
     where T: ToString
+{
+    naive_date_time_in(value, &Utc)
+}
+
+/// Converts string to utc naive date time, values without their own timezone are read in `tz`
+pub fn naive_date_time_in<T, Z>(value: T, tz: &Z) -> Option<NaiveDateTime>
+    where T: ToString,
+          Z: TimeZone
 {
     // Set value
     let value = value.to_string();
@@ -12,11 +20,6 @@ pub fn naive_date_time<T>(value: T) -> Option<NaiveDateTime>
         return Some(item.naive_utc());
     }
 
-    // Parse postgres date + time
-    if let Ok(item) = DateTime::parse_from_str(&format!("{}+00", &value), "%Y-%m-%dT%H:%M:%S%.f%#z") {
-        return Some(item.with_timezone(&Utc).naive_utc());
-    }
-
     // RFC3339 = Date + Time + TimeZone
     if let Ok(item) = DateTime::parse_from_rfc3339(&value) {
         return Some(item.with_timezone(&Utc).naive_utc());
@@ -27,19 +30,35 @@ pub fn naive_date_time<T>(value: T) -> Option<NaiveDateTime>
         return Some(item.naive_utc());
     }
 
-    // Date + Time only
-    if let Ok(item) = NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
-        return Some(item);
+    // Postgres date + time + timezone (e.g. `+00` or `+08:00`)
+    for format in ["%Y-%m-%dT%H:%M:%S%.f%#z", "%Y-%m-%d %H:%M:%S%.f%#z"] {
+        if let Ok(item) = DateTime::parse_from_str(&value, format) {
+            return Some(item.naive_utc());
+        }
     }
 
-    if let Ok(item) = NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
-        return Some(item);
+    // Postgres date + time or date + time only, read in the given timezone
+    for format in ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"] {
+        if let Ok(item) = NaiveDateTime::parse_from_str(&value, format) {
+            return local_to_utc(&item, tz);
+        }
     }
 
     // If none return None
     None
 }
 
+// Converts local time to utc, ambiguous times take the earliest instant and times skipped by a
+// daylight saving jump take the first instant after the gap
+fn local_to_utc<Z>(value: &NaiveDateTime, tz: &Z) -> Option<NaiveDateTime>
+    where Z: TimeZone
+{
+    (0..=24 * 60)
+        .map(|minutes| *value + Duration::minutes(minutes))
+        .find_map(|value| tz.from_local_datetime(&value).earliest())
+        .map(|item| item.naive_utc())
+}
+
 /// Converts string to naive date
 pub fn naive_date<T>(value: T) -> Option<NaiveDate>
     where T: ToString
@@ -76,4 +95,35 @@ pub fn naive_date<T>(value: T) -> Option<NaiveDate>
         Ok(item) => Some(item.date()),
         Err(_) => None
     }
-}
\ No newline at end of file
+}
+
+/// Converts timezone offset (`+08:00`, `+0800`, `-05`, `Z` or `UTC`) to fixed offset
+pub fn fixed_offset<T>(value: T) -> Option<FixedOffset>
+    where T: ToString
+{
+    let value = value.to_string();
+    let value = value.trim();
+
+    if value.is_empty() || value.eq_ignore_ascii_case("utc") || value.eq_ignore_ascii_case("z") {
+        return FixedOffset::east_opt(0);
+    }
+
+    let (sign, rest) = match value.split_at(1) {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None
+    };
+
+    let digits = rest.replace(':', "");
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (digits[..2].parse::<i32>().ok()?, digits[2..].parse::<i32>().ok()?),
+        _ => return None
+    };
+
+    if minutes >= 60 {
+        return None;
+    }
+
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
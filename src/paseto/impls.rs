@@ -1,10 +1,13 @@
 use actix_web::Result;
 use chrono::{DateTime, Duration, Utc};
+use paseto_lib::errors::GenericError;
 use paseto_lib::tokens::{validate_local_token, PasetoBuilder, TimeBackend};
 use serde::Serialize;
 
+use crate::ApiError;
 use crate::traits::GetI32;
 use crate::traits::IsEmpty;
+use crate::Message;
 use crate::Paseto;
 use crate::Payload;
 use crate::Token;
@@ -33,7 +36,7 @@ impl Paseto {
         // Decrypt access token signing
         let access_token_signing = base64_url::decode(&self.access_token_key_signing.clone().map_or(String::default(), |d| d.to_string()));
         if access_token_signing.is_err() {
-            return Err(Payload::error("Unable to generate access token"));
+            return Err(Payload::error(Message::new("auth.access_token_failed")));
         }
 
         // Set access token signing
@@ -49,7 +52,7 @@ impl Paseto {
             .build();
 
         if access_token.is_err() {
-            return Err(Payload::error("Unable to generate access token"));
+            return Err(Payload::error(Message::new("auth.access_token_failed")));
         }
 
         // Set refresh token duration
@@ -66,7 +69,7 @@ impl Paseto {
         // Decrypt refresh token signing
         let refresh_token_signing = base64_url::decode(&self.refresh_token_key_signing.clone().map_or(String::default(), |d| d.to_string()));
         if refresh_token_signing.is_err() {
-            return Err(Payload::error("Unable to generate access token"));
+            return Err(Payload::error(Message::new("auth.access_token_failed")));
         }
 
         // Set access token signing
@@ -82,13 +85,13 @@ impl Paseto {
             .build();
 
         if refresh_token.is_err() {
-            return Err(Payload::error("Unable to generate refresh token"));
+            return Err(Payload::error(Message::new("auth.refresh_token_failed")));
         }
 
         // Create encrypted web token
         let encrypted = crate::ciphers::encrypt(c.to_string().trim(), "WEB_KEY");
         if encrypted.is_err() {
-            return Err(Payload::error("Encryption failed"));
+            return Err(Payload::error(Message::new("auth.encryption_failed")));
         }
 
         // Create mutable token
@@ -109,7 +112,7 @@ impl Paseto {
         // Retrieve access token key signing
         let access_token_key_signing = match self.access_token_key_signing {
             Some(ref value) => value.to_string(),
-            None => return Err(Payload::error("Invalid authentication token"))
+            None => return Err(Payload::error(Message::new("auth.token_invalid")))
         };
 
         // Decrypt access token signing
@@ -121,7 +124,7 @@ impl Paseto {
         // Retrieve app name
         let app_name = match self.app_name {
             Some(ref value) => value.to_string(),
-            None => return Err(Payload::error("Invalid authentication token"))
+            None => return Err(Payload::error(Message::new("auth.token_invalid")))
         };
 
         // Verify token
@@ -132,23 +135,16 @@ impl Paseto {
             &TimeBackend::Chrono
         ) {
             Ok(value) => value,
-            Err(error) => {
-                let is_expired = error
-                    .to_string()
-                    .to_lowercase()
-                    .as_str() == "this token is expired (exp claim).";
-
-                return match is_expired {
-                    true => Err(Payload::error("Your authentication token has expired")),
-                    false => Err(Payload::error("Invalid authentication token"))
-                }
+            Err(error) => return match error.downcast_ref::<GenericError>() {
+                Some(GenericError::ExpiredToken {}) => Err(ApiError::AuthExpired(Message::new("auth.token_expired").to_string()).into()),
+                _ => Err(ApiError::AuthInvalid(Message::new("auth.token_invalid").to_string()).into())
             }
         };
 
         // Retrieve values from paseto
         let result = match result.get("data") {
             Some(value) => value.to_owned(),
-            None => return Err(Payload::error("Invalid authentication token"))
+            None => return Err(Payload::error(Message::new("auth.token_invalid")))
         };
 
         // Return value to custom struct
@@ -165,7 +161,7 @@ impl Paseto {
         // Decrypt refresh token signing
         let refresh_token_signing = base64_url::decode(&self.refresh_token_key_signing.clone().map_or(String::default(), |d| d.to_string()));
         if refresh_token_signing.is_err() {
-            return Err(Payload::error("Invalid refresh token"));
+            return Err(Payload::error(Message::new("auth.refresh_token_invalid")));
         }
 
         // Set access token signing
@@ -180,18 +176,11 @@ impl Paseto {
         ) {
             Ok(result) => match result.get("data") {
                 Some(_) => result,
-                None => return Err(Payload::error("Invalid refresh token"))
+                None => return Err(Payload::error(Message::new("auth.refresh_token_invalid")))
             },
-            Err(error) => {
-                let is_expired = error
-                    .to_string()
-                    .to_lowercase()
-                    .as_str() == "this token is expired (exp claim).";
-
-                return match is_expired {
-                    true => Err(Payload::error("Your refresh token has expired")),
-                    false => Err(Payload::error("Invalid refresh token"))
-                }
+            Err(error) => return match error.downcast_ref::<GenericError>() {
+                Some(GenericError::ExpiredToken {}) => Err(ApiError::AuthExpired(Message::new("auth.refresh_token_expired").to_string()).into()),
+                _ => Err(ApiError::AuthInvalid(Message::new("auth.refresh_token_invalid").to_string()).into())
             }
         };
 
@@ -199,13 +188,13 @@ impl Paseto {
         // Retrieve values from paseto
         let result = result.get("data");
         if result.is_none() {
-            return Err(Payload::error("Invalid refresh token"));
+            return Err(Payload::error(Message::new("auth.refresh_token_invalid")));
         }
 
         // Return value to custom struct
         let result:Result<C, _> = serde_json::from_value(result.unwrap().clone());
         if result.is_err() {
-            return Err(Payload::error("Invalid refresh token"));
+            return Err(Payload::error(Message::new("auth.refresh_token_invalid")));
         }
 
         // Return claims
@@ -219,13 +208,13 @@ impl Paseto {
         // Create decrypt web token
         let result = crate::ciphers::encrypt(token.to_string(), "WEB_KEY");
         if result.is_err() {
-            return Err(Payload::error("Decryption failed"));
+            return Err(Payload::error(Message::new("auth.decryption_failed")));
         }
 
         // Return value to custom struct
         let result:Result<C, _> = serde_json::from_str(&result.unwrap());
         if result.is_err() {
-            return Err(Payload::error("Invalid authentication token"));
+            return Err(Payload::error(Message::new("auth.token_invalid")));
         }
 
         // Return claims
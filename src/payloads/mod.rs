@@ -1,8 +1,17 @@
-use actix_web::{error, HttpRequest, HttpResponse, Responder};
+use actix_web::{error, HttpRequest, HttpResponse, HttpResponseBuilder, Responder};
 use actix_web::{body::BoxBody, http::StatusCode};
+use actix_web::http::header::{self, ContentEncoding, HeaderValue};
 use display_json::DisplayAsJsonPretty;
 use serde::{Serialize, Deserialize};
 
+use crate::errors::ApiError;
+use crate::locales::{Locale, Locales, Message, DEFAULT_LOCALE};
+use crate::pipelines::{Page, Pagination};
+
+pub mod problems;
+
+pub use problems::{Problem, ProblemMode};
+
 #[derive(Debug, Default, Clone, PartialEq, DisplayAsJsonPretty, Serialize, Deserialize)]
 pub struct Payload {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -16,71 +25,200 @@ pub struct Payload {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub errors: Option<serde_json::Value>
+    pub error_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<serde_json::Value>,
+    #[serde(skip)]
+    pub headers: Vec<(String, String)>,
+    #[serde(skip)]
+    pub is_etag: bool,
+    #[serde(skip)]
+    pub is_compress: Option<bool>
 }
 
 impl Responder for Payload {
     type Body = BoxBody;
 
-    fn respond_to(self, _req: &HttpRequest) -> HttpResponse<Self::Body> {
-        let payload = serde_json::to_string(&self).unwrap();
-
-        let mut code = 400;
-        match () {
-            _ if self.code.as_ref().is_some() => code = *self.code.as_ref().unwrap() as i32,
-            _ if self.code.as_ref().is_none() && !self.challenge.unwrap_or_default().is_empty() => code = 200,
-            _ => ()
-        }
-
-        match code {
-            200 => HttpResponse::Ok(),
-            401 => HttpResponse::Unauthorized(),
-            404 => HttpResponse::NotFound(),
-            500 => HttpResponse::InternalServerError(),
-            _ => HttpResponse::BadRequest()
-        }.content_type("application/json")
-            .body(payload)
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        self.respond(req)
     }
 }
 
 impl error::ResponseError for Payload {
     fn status_code(&self) -> StatusCode {
-        match self.code {
-            Some(200) => StatusCode::OK,
-            Some(400) => StatusCode::BAD_REQUEST,
-            Some(401) => StatusCode::UNAUTHORIZED,
-            Some(403) => StatusCode::FORBIDDEN,
-            Some(404) => StatusCode::NOT_FOUND,
-            Some(405) => StatusCode::METHOD_NOT_ALLOWED,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        self.get_status_code()
+    }
+
+    // Negotiated problem details and translations need the request, see `ProblemNegotiator`
+    fn error_response(&self) -> HttpResponse<BoxBody> {
+        match self.is_problem(None) {
+            true => Problem::from_payload(&self.render(), None).to_http_response(),
+            false => self.to_http_response()
+        }
+    }
+}
+
+impl Payload {
+    /// Returns the http status for `code`, any valid status code is honored. Without a code
+    /// the payload is a 200 when it carries a challenge and a 400 otherwise
+    pub fn get_status_code(&self) -> StatusCode {
+        if let Some(code) = self.code {
+            if let Ok(status_code) = StatusCode::from_u16(code) {
+                return status_code;
+            }
         }
 
+        match self.challenge.clone().unwrap_or_default().is_empty() {
+            true => StatusCode::BAD_REQUEST,
+            false => StatusCode::OK
+        }
     }
 
-    fn error_response(&self) -> HttpResponse<BoxBody> {
-        let payload = serde_json::to_string(&self).unwrap();
+    /// Converts payload to http response for the given request, localizing messages when
+    /// `Data<Locales>` is registered and honoring the problem mode
+    pub fn respond(&self, req: &HttpRequest) -> HttpResponse {
+        let locale = Locale::resolve(req);
+        let payload = self.localize(req);
+
+        let mut response = match payload.is_problem(Some(req)) {
+            true => Problem::from_payload(&payload, Some(req.path().to_string())).to_http_response(),
+            false => match payload.is_etag && payload.get_status_code().is_success() {
+                true => payload.to_etag_response(req),
+                false => payload.to_http_response()
+            }
+        };
 
-        let mut code = 400;
-        match () {
-            _ if self.code.as_ref().is_some() => code = *self.code.as_ref().unwrap() as i32,
-            _ if self.code.as_ref().is_none() && !self.challenge.clone().unwrap_or_default().is_empty() => code = 200,
-            _ => ()
+        // Shared caches need to know which request headers the body was negotiated on
+        if ProblemMode::get() == ProblemMode::Negotiate {
+            response.headers_mut().append(header::VARY, HeaderValue::from_static("Accept"));
         }
 
-        match code {
-            200 => HttpResponse::Ok(),
-            401 => HttpResponse::Unauthorized(),
-            403 => HttpResponse::Forbidden(),
-            404 => HttpResponse::NotFound(),
-            405 => HttpResponse::MethodNotAllowed(),
-            500 => HttpResponse::InternalServerError(),
-            _ => HttpResponse::BadRequest()
-        }.content_type("application/json")
-            .body(payload)
+        if let Some(locale) = locale {
+            response.headers_mut().append(header::VARY, HeaderValue::from_static("Accept-Language"));
+
+            if let Ok(value) = HeaderValue::from_str(&locale.0) {
+                response.headers_mut().insert(header::CONTENT_LANGUAGE, value);
+            }
+        }
+
+        response
+    }
+
+    // Responds with a strong etag of the body, or a 304 when it matches `If-None-Match`
+    fn to_etag_response(&self, req: &HttpRequest) -> HttpResponse {
+        let body = serde_json::to_string(&self).unwrap();
+        let etag = format!("\"{}\"", crate::ciphers::hash(&body));
+
+        // Weak comparison is used for If-None-Match (RFC 7232)
+        let is_match = req
+            .headers()
+            .get_all(header::IF_NONE_MATCH)
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|h| h.split(','))
+            .map(|tag| tag.trim().trim_start_matches("W/"))
+            .any(|tag| tag == "*" || tag == etag);
+
+        let mut builder = match is_match {
+            true => HttpResponse::NotModified(),
+            false => HttpResponse::build(self.get_status_code())
+        };
+
+        builder.insert_header((header::ETAG, etag));
+        self.apply_headers(&mut builder);
+
+        match is_match {
+            true => builder.finish(),
+            false => builder.content_type("application/json").body(body)
+        }
+    }
+
+    // Applies custom headers and compression preference
+    fn apply_headers(&self, builder: &mut HttpResponseBuilder) {
+        for (name, value) in &self.headers {
+            builder.insert_header((name.as_str(), value.as_str()));
+        }
+
+        // `Compress` middleware leaves responses that already declare an encoding alone
+        if self.is_compress == Some(false) {
+            builder.insert_header((header::CONTENT_ENCODING, ContentEncoding::Identity));
+        }
+    }
+
+    /// Adds a strong etag and answers matching `If-None-Match` requests with a 304
+    pub fn set_etag(&self, is_etag: bool) -> Self {
+        let mut data = self.clone();
+        data.is_etag = is_etag;
+        data
+    }
+
+    /// Opts out of (or back into) the `Compress` middleware for this response
+    pub fn set_compress(&self, is_compress: bool) -> Self {
+        let mut data = self.clone();
+        data.is_compress = Some(is_compress);
+        data
+    }
+
+    pub fn set_header<N, V>(&self, name: N, value: V) -> Self
+        where N: ToString,
+              V: ToString
+    {
+        let mut data = self.clone();
+        data.headers.retain(|(n, _)| !n.eq_ignore_ascii_case(&name.to_string()));
+        data.headers.push((name.to_string(), value.to_string()));
+        data
+    }
+
+    /// Renders message keys in message, error and errors using the registered `Locales`, or the
+    /// built in english messages when none is registered
+    pub fn localize(&self, req: &HttpRequest) -> Self {
+        match (Locales::from_request(req), Locale::resolve(req)) {
+            (Some(locales), Some(locale)) => self.localize_with(locales, &locale.0),
+            _ => self.render()
+        }
+    }
+
+    pub fn localize_with(&self, locales: &Locales, locale: &str) -> Self {
+        let mut data = self.clone();
+        data.message = data.message.map(|message| locales.translate(locale, message));
+        data.error = data.error.map(|error| locales.translate(locale, error));
+        data.errors = data.errors.map(|errors| locales.translate_value(locale, &errors));
+        data
+    }
+
+    // Renders message keys with the built in english messages, rendered text is left as is
+    fn render(&self) -> Self {
+        self.localize_with(&Locales::default(), DEFAULT_LOCALE)
+    }
+
+    /// Checks if payload is an error that should be sent as problem details under the
+    /// global `ProblemMode`, see `Payload::set_problem_mode`
+    pub fn is_problem(&self, req: Option<&HttpRequest>) -> bool {
+        self.get_status_code().as_u16() >= 400 && ProblemMode::get().is_problem(req)
+    }
+
+    pub fn set_problem_mode(mode: ProblemMode) {
+        ProblemMode::set(mode);
+    }
+
+    /// Converts payload to json http response using `get_status_code`
+    pub fn to_http_response(&self) -> HttpResponse {
+        let payload = self.render();
+        let status_code = payload.get_status_code();
+        let mut builder = HttpResponse::build(status_code);
+        self.apply_headers(&mut builder);
+
+        // These statuses can't carry a body
+        if status_code == StatusCode::NO_CONTENT || status_code == StatusCode::NOT_MODIFIED {
+            return builder.finish();
+        }
+
+        builder
+            .content_type("application/json")
+            .body(serde_json::to_string(&payload).unwrap())
     }
-}
 
-impl Payload {
     pub fn data<T>(code:usize, data:T) -> Self
         where T: Serialize
     {
@@ -93,16 +231,9 @@ impl Payload {
     }
 
     pub fn error<T: ToString>(error: T) -> error::Error {
-        let error = error.to_string();
-
-        let code = match error.to_lowercase().as_str() == "your authentication token has expired" {
-            true => Some(401),
-            false => Some(400)
-        };
-
         Self {
-            code,
-            error: Some(error),
+            code: Some(400),
+            error: Some(error.to_string()),
             ..Default::default()
         }.into()
     }
@@ -118,6 +249,97 @@ impl Payload {
         }.into()
     }
 
+    /// Creates payload with the page list as data along with pagination metadata, next/prev are also
+    /// sent as a `Link` header and the total as `X-Total-Count`
+    pub fn page<T>(req: &HttpRequest, page: &Page<T>) -> Self
+        where T: Serialize
+    {
+        let pagination = page.pagination(req);
+
+        let links = [("next", &pagination.links.next), ("prev", &pagination.links.prev)]
+            .iter()
+            .filter_map(|(rel, link)| link.as_ref().map(|link| format!("<{link}>; rel=\"{rel}\"")))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        let payload = Self {
+            code: Some(200),
+            data: Some(serde_json::to_value(&page.list)
+                .unwrap_or(serde_json::Value::Null)),
+            pagination: Some(pagination.clone()),
+            ..Default::default()
+        }.set_header("X-Total-Count", pagination.total);
+
+        match links.is_empty() {
+            true => payload,
+            false => payload.set_header("Link", links)
+        }
+    }
+
+    pub fn created<T>(data: T) -> Self
+        where T: Serialize
+    {
+        Self::data(201, data)
+    }
+
+    pub fn accepted<T: ToString>(message: T) -> Self {
+        Self {
+            code: Some(202),
+            message: Some(message.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn no_content() -> Self {
+        Self {
+            code: Some(204),
+            ..Default::default()
+        }
+    }
+
+    pub fn status<T: ToString>(code: u16, error: T) -> error::Error {
+        Self {
+            code: Some(code),
+            error: Some(error.to_string()),
+            ..Default::default()
+        }.into()
+    }
+
+    pub fn unauthorized<T: ToString>(error: T) -> error::Error {
+        Self::status(401, error)
+    }
+
+    pub fn forbidden<T: ToString>(error: T) -> error::Error {
+        Self::status(403, error)
+    }
+
+    pub fn not_found<T: ToString>(error: T) -> error::Error {
+        Self::status(404, error)
+    }
+
+    pub fn conflict<T: ToString>(error: T) -> error::Error {
+        Self::status(409, error)
+    }
+
+    pub fn unprocessable_entity<T>(errors: T) -> error::Error
+        where T: Serialize
+    {
+        Self {
+            code: Some(422),
+            errors: Some(serde_json::to_value(errors)
+                .unwrap_or(serde_json::Value::Null)),
+            ..Default::default()
+        }.into()
+    }
+
+    pub fn too_many_requests<T: ToString>(error: T) -> error::Error {
+        Self::status(429, error)
+    }
+
+    pub fn service_unavailable<T: ToString>(error: T) -> error::Error {
+        Self::status(503, error)
+    }
+
     pub fn success<T: ToString>(message: T) -> Self {
         Self {
             code: Some(200),
@@ -127,38 +349,36 @@ impl Payload {
     }
 
     pub fn authentication_expired() -> HttpResponse {
+        Self::from(ApiError::AuthExpired(Message::new("auth.expired").to_string())).to_http_response()
+    }
+
+    pub fn csrf() -> HttpResponse {
         let payload = Self {
-            code: Some(401),
-            error: Some(String::from("Authentication token expired")),
+            code: Some(403),
+            error: Some(Message::new("auth.csrf_invalid").to_string()),
             ..Default::default()
         };
 
-        HttpResponse::BadRequest()
-            .content_type("application/json")
-            .body(serde_json::to_string(&payload).unwrap())
+        payload.to_http_response()
     }
 
     pub fn database() -> HttpResponse {
         let payload = Self {
             code: Some(400),
-            error: Some(String::from("Invalid database configuration")),
+            error: Some(Message::new("server.database_invalid").to_string()),
             ..Default::default()
         };
 
-        HttpResponse::BadRequest()
-            .content_type("application/json")
-            .body(serde_json::to_string(&payload).unwrap())
+        payload.to_http_response()
     }
 
     pub fn middleware() -> HttpResponse {
         let payload = Self {
             code: Some(400),
-            error: Some(String::from("Missing middleware. Please configure your server properly")),
+            error: Some(Message::new("server.middleware_missing").to_string()),
             ..Default::default()
         };
 
-        HttpResponse::BadRequest()
-            .content_type("application/json")
-            .body(serde_json::to_string(&payload).unwrap())
+        payload.to_http_response()
     }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use actix_web::{HttpRequest, HttpResponse};
+use actix_web::http::{header, StatusCode};
+use serde::{Serialize, Deserialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::Payload;
+
+pub static PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+// Global problem mode, stored as u8 so it can be switched without locking
+static PROBLEM_MODE: AtomicU8 = AtomicU8::new(0);
+
+/// Problem mode decides when error payloads are sent as RFC 7807 problem details.
+/// `Negotiate` only does so when the client lists `application/problem+json` in `Accept`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ProblemMode {
+    #[default]
+    Disabled,
+    Always,
+    Negotiate,
+}
+
+impl ProblemMode {
+    pub fn get() -> Self {
+        match PROBLEM_MODE.load(Ordering::Relaxed) {
+            1 => Self::Always,
+            2 => Self::Negotiate,
+            _ => Self::Disabled
+        }
+    }
+
+    pub fn set(mode: Self) {
+        let value = match mode {
+            Self::Disabled => 0,
+            Self::Always => 1,
+            Self::Negotiate => 2,
+        };
+
+        PROBLEM_MODE.store(value, Ordering::Relaxed);
+    }
+
+    // Checks if the response for this request should be a problem document
+    pub fn is_problem(&self, req: Option<&HttpRequest>) -> bool {
+        match self {
+            Self::Disabled => false,
+            Self::Always => true,
+            Self::Negotiate => req.map(accepts_problem).unwrap_or(false)
+        }
+    }
+}
+
+// Create problem details struct (RFC 7807)
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub title: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<serde_json::Value>,
+}
+
+impl Problem {
+    /// Converts payload into problem details, `instance` is usually the request path
+    pub fn from_payload(payload: &Payload, instance: Option<String>) -> Self {
+        let status = payload.get_status_code();
+
+        Self {
+            kind: String::from("about:blank"),
+            title: status.canonical_reason().unwrap_or("Unknown Error").to_string(),
+            status: status.as_u16(),
+            detail: payload.error.clone().or(payload.message.clone()),
+            instance,
+            code: payload.error_code.clone(),
+            errors: payload.errors.clone(),
+        }
+    }
+
+    pub fn to_http_response(&self) -> HttpResponse {
+        let status_code = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+        HttpResponse::build(status_code)
+            .content_type(PROBLEM_CONTENT_TYPE)
+            .body(serde_json::to_string(&self).unwrap())
+    }
+}
+
+/// Checks if `Accept` header lists `application/problem+json`
+pub fn accepts_problem(req: &HttpRequest) -> bool {
+    req.headers()
+        .get_all(header::ACCEPT)
+        .filter_map(|h| h.to_str().ok())
+        .flat_map(|h| h.split(','))
+        .filter_map(|h| h.split(';').next())
+        .any(|h| h.trim().eq_ignore_ascii_case(PROBLEM_CONTENT_TYPE))
+}
@@ -0,0 +1,123 @@
+use actix_web::Result;
+use mongodb::bson::{doc, Bson, Document};
+use serde::{Deserialize, Serialize};
+
+use crate::Message;
+use crate::Payload;
+
+/// Opaque keyset cursor holding the sort values of the last returned document followed by
+/// its `_id`. Encoded as url safe base64 of a bson document so object ids and dates survive
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PageCursor {
+    pub values: Vec<Bson>,
+}
+
+// Create keyset page result
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CursorPage<T> {
+    pub list: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    pub has_more: bool,
+}
+
+/// Normalizes sort fields for keyset pagination, `_id` is moved to the end to break ties and
+/// follows the direction of the last sort field
+pub(crate) fn cursor_fields<T>(sort_fields: &[(T, i32)]) -> Vec<(String, i32)>
+    where T: ToString
+{
+    let mut fields = sort_fields
+        .iter()
+        .map(|(field, order)| (field.to_string(), if *order < 0 { -1 } else { 1 }))
+        .filter(|(field, _)| field != "_id")
+        .collect::<Vec<(String, i32)>>();
+
+    let id_order = fields.last().map(|(_, order)| *order).unwrap_or(1);
+    fields.push((String::from("_id"), id_order));
+    fields
+}
+
+impl PageCursor {
+    /// Creates cursor from a raw document using the sort fields (dotted paths allowed) and `_id`,
+    /// missing values are kept as null
+    pub fn from_document<T>(document: &Document, sort_fields: &[(T, i32)]) -> Self
+        where T: ToString
+    {
+        let values = cursor_fields(sort_fields)
+            .iter()
+            .map(|(field, _)| get_path(document, field))
+            .collect::<Vec<Bson>>();
+
+        Self { values }
+    }
+
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::new();
+        let document = doc! { "v": self.values.clone() };
+
+        match document.to_writer(&mut bytes) {
+            Ok(_) => base64_url::encode(&bytes),
+            Err(_) => String::default()
+        }
+    }
+
+    pub fn decode<T>(cursor: T) -> Result<Self>
+        where T: ToString
+    {
+        let bytes = match base64_url::decode(&cursor.to_string()) {
+            Ok(bytes) => bytes,
+            Err(_) => return Err(Payload::error(Message::new("request.invalid_cursor")))
+        };
+
+        let document = match Document::from_reader(&mut bytes.as_slice()) {
+            Ok(document) => document,
+            Err(_) => return Err(Payload::error(Message::new("request.invalid_cursor")))
+        };
+
+        let values = match document.get_array("v") {
+            Ok(values) => values.clone(),
+            Err(_) => return Err(Payload::error(Message::new("request.invalid_cursor")))
+        };
+
+        // Only plain values are accepted so a crafted cursor can't carry query operators
+        match values.iter().all(is_scalar) {
+            true => Ok(Self { values }),
+            false => Err(Payload::error(Message::new("request.invalid_cursor")))
+        }
+    }
+}
+
+// Checks if value is a plain sortable value
+fn is_scalar(value: &Bson) -> bool {
+    matches!(
+        value,
+        Bson::Null
+            | Bson::Boolean(_)
+            | Bson::Int32(_)
+            | Bson::Int64(_)
+            | Bson::Double(_)
+            | Bson::Decimal128(_)
+            | Bson::String(_)
+            | Bson::ObjectId(_)
+            | Bson::DateTime(_)
+            | Bson::Timestamp(_)
+    )
+}
+
+// Retrieves value at a dotted path e.g. `profile.created_at`
+fn get_path(document: &Document, path: &str) -> Bson {
+    let mut parts = path.split('.');
+    let mut value = match parts.next().and_then(|part| document.get(part)) {
+        Some(value) => value,
+        None => return Bson::Null
+    };
+
+    for part in parts {
+        value = match value.as_document().and_then(|document| document.get(part)) {
+            Some(value) => value,
+            None => return Bson::Null
+        };
+    }
+
+    value.clone()
+}
@@ -0,0 +1,275 @@
+use actix_web::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use mongodb::bson::{doc, Bson, DateTime as BsonDateTime, Document};
+
+use crate::parsers::dates::{fixed_offset, naive_date_time_in};
+use crate::pipelines::Pipeline;
+use crate::Message;
+use crate::Payload;
+
+// Create time bucket unit for `time_series`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TimeUnit {
+    Minute,
+    Hour,
+    #[default]
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl TimeUnit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Minute => "minute",
+            Self::Hour => "hour",
+            Self::Day => "day",
+            Self::Week => "week",
+            Self::Month => "month",
+            Self::Quarter => "quarter",
+            Self::Year => "year",
+        }
+    }
+
+    // Start of the bucket holding `value`, weeks start on sunday like `$dateTrunc`
+    fn truncate(&self, value: NaiveDateTime) -> NaiveDateTime {
+        let date = value.date();
+        let start = match self {
+            Self::Minute => return value.date().and_hms_opt(value.hour(), value.minute(), 0).unwrap_or(value),
+            Self::Hour => return value.date().and_hms_opt(value.hour(), 0, 0).unwrap_or(value),
+            Self::Day => Some(date),
+            Self::Week => Some(date - Duration::days(date.weekday().num_days_from_sunday() as i64)),
+            Self::Month => NaiveDate::from_ymd_opt(date.year(), date.month(), 1),
+            Self::Quarter => NaiveDate::from_ymd_opt(date.year(), (date.month() - 1) / 3 * 3 + 1, 1),
+            Self::Year => NaiveDate::from_ymd_opt(date.year(), 1, 1),
+        };
+
+        start.and_then(|date| date.and_hms_opt(0, 0, 0)).unwrap_or(value)
+    }
+
+    // Start of the bucket after the one starting at `value`
+    fn next(&self, value: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Self::Minute => value + Duration::minutes(1),
+            Self::Hour => value + Duration::hours(1),
+            Self::Day => value + Duration::days(1),
+            Self::Week => value + Duration::weeks(1),
+            Self::Month => value.checked_add_months(Months::new(1)).unwrap_or(value),
+            Self::Quarter => value.checked_add_months(Months::new(3)).unwrap_or(value),
+            Self::Year => value.checked_add_months(Months::new(12)).unwrap_or(value),
+        }
+    }
+}
+
+// Parsed bounds of a date range, `series` holds the wall clock bucket bounds of `time_series`
+#[derive(Debug, Default)]
+struct DateBounds {
+    from: Option<BsonDateTime>,
+    to: Option<(BsonDateTime, bool)>,
+    series: Option<(BsonDateTime, BsonDateTime)>,
+}
+
+impl Pipeline {
+    /// Matches records whose date field is between `from` and `to` (utc), see `date_range_tz`
+    pub fn date_range<F, T, U>(&mut self, field: F, from: T, to: U) -> Result<&mut Self>
+        where F: ToString,
+              T: ToString,
+              U: ToString
+    {
+        self.date_range_tz(field, from, to, "UTC")
+    }
+
+    /// Matches records whose date field is between `from` and `to`. Values are read with
+    /// `naive_date_time` formats or as plain `%Y-%m-%d` dates, those without their own timezone
+    /// are read in `tz` (an offset such as `+08:00` or an Olson name such as `Asia/Manila`). An
+    /// empty value leaves that side open and a plain date as `to` includes that whole day.
+    /// Invalid values or timezones are rejected
+    pub fn date_range_tz<F, T, U, Z>(&mut self, field: F, from: T, to: U, tz: Z) -> Result<&mut Self>
+        where F: ToString,
+              T: ToString,
+              U: ToString,
+              Z: ToString
+    {
+        let bounds = date_bounds(&from.to_string(), &to.to_string(), &tz.to_string(), None)?;
+        Ok(self.match_date_bounds(field.to_string(), &bounds))
+    }
+
+    /// Counts records per `unit` of the date field between `from` and `to` (read like
+    /// `date_range_tz`) as `{ date, count }` sorted by date. Every bucket between `from` and `to`
+    /// is returned with a count of 0 when empty, with an open side only the gaps between the first
+    /// and last bucket are filled. `tz` accepts offsets or Olson names (e.g. `Asia/Manila`) and
+    /// buckets are filled on local wall clock dates so they stay aligned across daylight saving
+    /// changes. Requires MongoDB 5.1+ for `$dateTrunc` and `$densify`
+    pub fn time_series<F, T, U, Z>(&mut self, field: F, unit: TimeUnit, tz: Z, from: T, to: U) -> Result<&mut Self>
+        where F: ToString,
+              T: ToString,
+              U: ToString,
+              Z: ToString
+    {
+        let field = field.to_string();
+        let tz = Some(tz.to_string().trim().to_string())
+            .filter(|tz| !tz.is_empty())
+            .unwrap_or(String::from("UTC"));
+
+        let bounds = date_bounds(&from.to_string(), &to.to_string(), &tz, Some(unit))?;
+        self.match_date_bounds(field.clone(), &bounds);
+
+        self.queries.push(doc! {
+            "$match": { field.clone(): { "$type": "date" } }
+        });
+
+        // Local wall clock time stored as utc, so truncating and stepping never cross a dst change
+        let wall_clock = date_from_parts(doc! { "$dateToParts": { "date": format!("${field}"), "timezone": tz.clone() } }, None);
+
+        self.queries.push(doc! {
+            "$group": {
+                "_id": {
+                    "$dateTrunc": {
+                        "date": wall_clock,
+                        "unit": unit.as_str()
+                    }
+                },
+                "count": { "$sum": 1 }
+            }
+        });
+
+        let densify_bounds = match bounds.series {
+            Some((lower, upper)) => Bson::Array(vec![Bson::DateTime(lower), Bson::DateTime(upper)]),
+            None => Bson::String(String::from("full"))
+        };
+
+        self.queries.push(doc! {
+            "$densify": {
+                "field": "_id",
+                "range": { "step": 1, "unit": unit.as_str(), "bounds": densify_bounds }
+            }
+        });
+
+        // Back to the actual instant the local bucket starts
+        self.queries.push(doc! {
+            "$project": {
+                "_id": 0,
+                "date": date_from_parts(doc! { "$dateToParts": { "date": "$_id" } }, Some(tz)),
+                "count": { "$ifNull": ["$count", 0] }
+            }
+        });
+
+        self.queries.push(doc! {
+            "$sort": { "date": 1 }
+        });
+
+        Ok(self)
+    }
+
+    // Adds the match of parsed date bounds, nothing is added when both sides are open
+    fn match_date_bounds(&mut self, field: String, bounds: &DateBounds) -> &mut Self {
+        let mut range = Document::new();
+
+        if let Some(from) = bounds.from {
+            range.insert("$gte", from);
+        }
+
+        match bounds.to {
+            Some((to, true)) => { range.insert("$lt", to); },
+            Some((to, false)) => { range.insert("$lte", to); },
+            None => {}
+        }
+
+        if !range.is_empty() {
+            self.queries.push(doc! {
+                "$match": { field: range }
+            });
+        }
+
+        self
+    }
+}
+
+// Parses date range in an offset or Olson timezone, bucket bounds are added when `unit` is given
+fn date_bounds(from: &str, to: &str, tz: &str, unit: Option<TimeUnit>) -> Result<DateBounds> {
+    match (fixed_offset(tz), tz.trim().parse::<Tz>()) {
+        (Some(offset), _) => date_bounds_in(from, to, &offset, unit),
+        (None, Ok(tz)) => date_bounds_in(from, to, &tz, unit),
+        (None, Err(_)) => Err(Payload::error(Message::new("request.invalid_timezone").set_arg("timezone", tz)))
+    }
+}
+
+fn date_bounds_in<Z>(from: &str, to: &str, tz: &Z, unit: Option<TimeUnit>) -> Result<DateBounds>
+    where Z: TimeZone
+{
+    let from = date_bound(from.trim(), tz, false)?.map(|(from, _)| from);
+    let to = date_bound(to.trim(), tz, true)?;
+
+    // Bucket of the first and past the last matched instant, on the wall clock like `time_series`
+    let wall_clock = |value: NaiveDateTime| Utc.from_utc_datetime(&value).with_timezone(tz).naive_local();
+    let series = match (unit, from, to) {
+        (Some(unit), Some(from), Some((to, is_exclusive))) => {
+            let last = if is_exclusive { to - Duration::milliseconds(1) } else { to };
+            let lower = unit.truncate(wall_clock(from));
+            let upper = unit.next(unit.truncate(wall_clock(last)));
+
+            Some((to_bson_date_time(lower), to_bson_date_time(upper)))
+        },
+        _ => None
+    };
+
+    Ok(DateBounds {
+        from: from.map(to_bson_date_time),
+        to: to.map(|(to, is_exclusive)| (to_bson_date_time(to), is_exclusive)),
+        series,
+    })
+}
+
+// Parses one side of a date range as utc `(date, is_date_only)`, an empty value is open
+fn date_bound<Z>(value: &str, tz: &Z, is_end: bool) -> Result<Option<(NaiveDateTime, bool)>>
+    where Z: TimeZone
+{
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+    let date_time = match date {
+        // A plain date as the end includes that whole day
+        Some(date) => {
+            let date = if is_end { date + Duration::days(1) } else { date };
+            naive_date_time_in(format!("{date} 00:00:00"), tz)
+        },
+        None => naive_date_time_in(value, tz)
+    };
+
+    match date_time {
+        Some(date_time) => Ok(Some((date_time, date.is_some()))),
+        None => Err(Payload::error(Message::new("request.invalid_date").set_arg("value", value)))
+    }
+}
+
+// Converts utc naive date time to bson date time
+fn to_bson_date_time(value: NaiveDateTime) -> BsonDateTime {
+    BsonDateTime::from_chrono(Utc.from_utc_datetime(&value))
+}
+
+// Builds a date from the parts of a `$dateToParts` expression, read in `tz` when given
+fn date_from_parts(parts: Document, tz: Option<String>) -> Document {
+    let mut date = doc! {
+        "year": "$$parts.year",
+        "month": "$$parts.month",
+        "day": "$$parts.day",
+        "hour": "$$parts.hour",
+        "minute": "$$parts.minute"
+    };
+
+    if let Some(tz) = tz {
+        date.insert("timezone", tz);
+    }
+
+    doc! {
+        "$let": {
+            "vars": { "parts": parts },
+            "in": { "$dateFromParts": date }
+        }
+    }
+}
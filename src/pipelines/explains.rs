@@ -0,0 +1,75 @@
+use mongodb::bson::{Bson, Document};
+use serde::Serialize;
+
+/// Summary of an aggregation execution plan returned by `Pipeline::explain`. Plans differ
+/// between server versions and sharded clusters so values are gathered from the whole document
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct Explain {
+    // Aggregation stages as executed e.g. `$cursor`, `$group`
+    pub pipeline_stages: Vec<String>,
+    // Query plan stages e.g. `IXSCAN`, `FETCH`, `COLLSCAN`
+    pub plan_stages: Vec<String>,
+    pub indexes: Vec<String>,
+    pub is_collection_scan: bool,
+    pub docs_examined: i64,
+    pub keys_examined: i64,
+    pub returned: i64,
+    pub execution_time_ms: i64,
+    #[serde(skip)]
+    pub raw: Document,
+}
+
+impl Explain {
+    pub fn from_document(document: Document) -> Self {
+        let mut data = Self::default();
+
+        if let Ok(stages) = document.get_array("stages") {
+            data.pipeline_stages = stages
+                .iter()
+                .filter_map(|stage| stage.as_document())
+                .filter_map(|stage| stage.keys().next().cloned())
+                .collect();
+        }
+
+        data.walk(&document);
+        data.is_collection_scan = data.plan_stages.iter().any(|stage| stage == "COLLSCAN");
+        data.raw = document;
+        data
+    }
+
+    // Collects plan details found anywhere in the explain output
+    fn walk(&mut self, document: &Document) {
+        for (key, value) in document {
+            match (key.as_str(), value) {
+                // Rejected plans weren't executed
+                ("rejectedPlans", _) => continue,
+                ("stage", Bson::String(stage)) => self.plan_stages.push(stage.clone()),
+                ("indexName", Bson::String(index)) if !self.indexes.contains(index) => self.indexes.push(index.clone()),
+                // Execution stages repeat the winning plan so only the totals are read
+                ("executionStats", Bson::Document(stats)) => {
+                    self.docs_examined += get_i64(stats, "totalDocsExamined");
+                    self.keys_examined += get_i64(stats, "totalKeysExamined");
+                    self.returned = self.returned.max(get_i64(stats, "nReturned"));
+                    self.execution_time_ms = self.execution_time_ms.max(get_i64(stats, "executionTimeMillis"));
+                },
+                (_, Bson::Document(child)) => self.walk(child),
+                (_, Bson::Array(items)) => for item in items {
+                    if let Bson::Document(child) = item {
+                        self.walk(child);
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+// Retrieves number regardless of its bson integer type
+fn get_i64(document: &Document, key: &str) -> i64 {
+    match document.get(key) {
+        Some(Bson::Int32(value)) => *value as i64,
+        Some(Bson::Int64(value)) => *value,
+        Some(Bson::Double(value)) => *value as i64,
+        _ => 0
+    }
+}
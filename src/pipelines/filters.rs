@@ -0,0 +1,347 @@
+use actix_web::Result;
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use std::str::FromStr;
+
+use crate::Message;
+use crate::Payload;
+
+/// User input longer than this is rejected by `MatchMode::try_to_pattern`
+pub static MAX_PATTERN_LENGTH: usize = 128;
+
+/// How user input is matched when it becomes a regex, input is always escaped
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    Prefix,
+    #[default]
+    Contains,
+    ExactCi,
+}
+
+impl MatchMode {
+    /// Escapes `value` and anchors it for the mode. Over-long input is truncated for `Prefix` and
+    /// `Contains`, which only widens the match, and kept whole for `ExactCi`
+    pub fn to_pattern<T>(&self, value: T) -> String
+        where T: ToString
+    {
+        let value = value.to_string();
+        let value = value.trim();
+        let value = match (self, value.char_indices().nth(MAX_PATTERN_LENGTH)) {
+            (Self::Prefix | Self::Contains, Some((index, _))) => &value[..index],
+            _ => value
+        };
+
+        self.anchor(value)
+    }
+
+    /// Same as `to_pattern` but rejects input longer than `MAX_PATTERN_LENGTH` in every mode
+    pub fn try_to_pattern<T>(&self, value: T) -> Result<String>
+        where T: ToString
+    {
+        let value = value.to_string();
+        let value = value.trim();
+
+        match value.chars().count() > MAX_PATTERN_LENGTH {
+            true => Err(Payload::error(Message::new("request.search_too_long").set_arg("max", MAX_PATTERN_LENGTH))),
+            false => Ok(self.anchor(value))
+        }
+    }
+
+    // Escapes value and anchors it for the mode
+    fn anchor(&self, value: &str) -> String {
+        let value = regex::escape(value);
+
+        match self {
+            Self::Prefix => format!("^{value}"),
+            Self::Contains => value,
+            Self::ExactCi => format!("^{value}$"),
+        }
+    }
+}
+
+/// Typed filter expression compiled into a `$match` document by `Pipeline::filter`.
+/// Values keep their bson types, build them with the constructors e.g.
+/// `Filter::and(vec![Filter::eq("status", "Enabled"), Filter::gte("age", 18)])`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Eq(String, Bson),
+    Ne(String, Bson),
+    In(String, Vec<Bson>),
+    Range {
+        field: String,
+        gte: Option<Bson>,
+        lte: Option<Bson>,
+    },
+    Regex {
+        field: String,
+        pattern: String,
+        is_case_insensitive: bool,
+    },
+    Exists(String, bool),
+    ElemMatch(String, Box<Filter>),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn eq<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: Into<Bson>
+    {
+        Self::Eq(field.to_string(), value.into())
+    }
+
+    pub fn ne<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: Into<Bson>
+    {
+        Self::Ne(field.to_string(), value.into())
+    }
+
+    pub fn is_in<F, I, V>(field: F, values: I) -> Self
+        where F: ToString,
+              I: IntoIterator<Item = V>,
+              V: Into<Bson>
+    {
+        Self::In(field.to_string(), values.into_iter().map(Into::into).collect())
+    }
+
+    // Inclusive range, either bound may be left open and a range without bounds is omitted
+    pub fn range<F, V>(field: F, gte: Option<V>, lte: Option<V>) -> Self
+        where F: ToString,
+              V: Into<Bson>
+    {
+        Self::Range {
+            field: field.to_string(),
+            gte: gte.map(Into::into),
+            lte: lte.map(Into::into),
+        }
+    }
+
+    pub fn gte<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: Into<Bson>
+    {
+        Self::range(field, Some(value), None)
+    }
+
+    pub fn lte<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: Into<Bson>
+    {
+        Self::range(field, None, Some(value))
+    }
+
+    /// Case insensitive match of `value` anywhere in the field, regex characters are escaped
+    pub fn regex<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        Self::regex_mode(field, value, MatchMode::Contains)
+    }
+
+    pub fn regex_mode<F, V>(field: F, value: V, mode: MatchMode) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        Self::Regex {
+            field: field.to_string(),
+            pattern: mode.to_pattern(value),
+            is_case_insensitive: true,
+        }
+    }
+
+    // Case insensitive "starts with", can use an index unlike the other modes
+    pub fn prefix<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        Self::regex_mode(field, value, MatchMode::Prefix)
+    }
+
+    pub fn contains<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        Self::regex_mode(field, value, MatchMode::Contains)
+    }
+
+    pub fn exact_ci<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        Self::regex_mode(field, value, MatchMode::ExactCi)
+    }
+
+    pub fn exists<F>(field: F, is_exists: bool) -> Self
+        where F: ToString
+    {
+        Self::Exists(field.to_string(), is_exists)
+    }
+
+    // Matches arrays with at least one element satisfying the filter (fields relative to the element)
+    pub fn elem_match<F>(field: F, filter: Filter) -> Self
+        where F: ToString
+    {
+        Self::ElemMatch(field.to_string(), Box::new(filter))
+    }
+
+    pub fn and(filters: Vec<Filter>) -> Self {
+        Self::And(filters)
+    }
+
+    // An empty `or` matches nothing, a member without constraints makes it match everything
+    pub fn or(filters: Vec<Filter>) -> Self {
+        Self::Or(filters)
+    }
+
+    pub fn negate(filter: Filter) -> Self {
+        Self::Not(Box::new(filter))
+    }
+
+    /// Equality on an object id field, an invalid id matches nothing
+    pub fn object_id<F, V>(field: F, value: V) -> Self
+        where F: ToString,
+              V: ToString
+    {
+        match ObjectId::from_str(value.to_string().trim()) {
+            Ok(object_id) => Self::eq(field, object_id),
+            Err(_) => Self::is_in(field, Vec::<Bson>::new())
+        }
+    }
+
+    /// Converts `match_and` style tuples, `_id` and flagged fields become object ids and only
+    /// the fields listed in `regex_fields` use a case insensitive regex
+    pub fn from_tuples<T, U, R>(fields: Vec<(T, U, bool)>, regex_fields: &[R]) -> Vec<Self>
+        where T: ToString,
+              U: ToString,
+              R: ToString
+    {
+        let regex_fields = regex_fields.iter().map(|f| f.to_string()).collect::<Vec<String>>();
+
+        fields
+            .into_iter()
+            .map(|(field, value, is_object_id)| {
+                let field = field.to_string();
+                let value = value.to_string();
+
+                match (is_object_id || field == "_id", value.trim().is_empty()) {
+                    (true, _) => Self::object_id(field, value),
+                    (false, true) => Self::exists(field, false),
+                    (false, false) => match regex_fields.contains(&field) {
+                        true => Self::regex(field, value),
+                        false => Self::eq(field, value)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn to_document(&self) -> Document {
+        match self {
+            Self::Eq(field, value) => doc! { field: value.clone() },
+            Self::Ne(field, value) => doc! { field: { "$ne": value.clone() } },
+            Self::In(field, values) => doc! { field: { "$in": values.clone() } },
+            Self::Range { field, gte, lte } => {
+                let mut range = Document::new();
+
+                if let Some(gte) = gte {
+                    range.insert("$gte", gte.clone());
+                }
+
+                if let Some(lte) = lte {
+                    range.insert("$lte", lte.clone());
+                }
+
+                match range.is_empty() {
+                    true => Document::new(),
+                    false => doc! { field: range }
+                }
+            },
+            Self::Regex { field, pattern, is_case_insensitive } => match is_case_insensitive {
+                true => doc! { field: { "$regex": pattern.clone(), "$options": "i" } },
+                false => doc! { field: { "$regex": pattern.clone() } }
+            },
+            Self::Exists(field, is_exists) => doc! { field: { "$exists": *is_exists } },
+            Self::ElemMatch(field, filter) => doc! { field: { "$elemMatch": filter.to_document() } },
+            Self::And(filters) => {
+                // Members without constraints don't narrow an `and`
+                let mut filters = filters
+                    .iter()
+                    .map(|f| f.to_document())
+                    .filter(|f| !f.is_empty())
+                    .collect::<Vec<Document>>();
+
+                match filters.len() {
+                    0 => Document::new(),
+                    1 => filters.remove(0),
+                    _ => doc! { "$and": filters }
+                }
+            },
+            Self::Or(filters) => {
+                let mut filters = filters.iter().map(|f| f.to_document()).collect::<Vec<Document>>();
+
+                // A member without constraints matches everything and so does the whole `or`
+                match (filters.len(), filters.iter().any(|f| f.is_empty())) {
+                    (0, _) => doc! { "_id": { "$in": Bson::Array(Vec::new()) } },
+                    (_, true) => Document::new(),
+                    (1, _) => filters.remove(0),
+                    _ => doc! { "$or": filters }
+                }
+            },
+            // `$not` only works on field operators, `$nor` negates a whole expression
+            Self::Not(filter) => doc! { "$nor": [filter.to_document()] },
+        }
+    }
+}
+
+// Allows `!Filter::eq(..)`
+impl std::ops::Not for Filter {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self::negate(self)
+    }
+}
+
+impl From<Filter> for Document {
+    fn from(filter: Filter) -> Self {
+        filter.to_document()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn and_drops_empty_members() {
+        let filter = Filter::and(vec![Filter::range("x", None::<i32>, None), Filter::eq("y", 1)]);
+        assert_eq!(filter.to_document(), doc! { "y": 1 });
+        assert_eq!(Filter::and(vec![]).to_document(), Document::new());
+    }
+
+    #[test]
+    fn or_with_empty_member_matches_everything() {
+        let filter = Filter::or(vec![Filter::range("x", None::<i32>, None), Filter::eq("y", 1)]);
+        assert_eq!(filter.to_document(), Document::new());
+        assert_eq!(Filter::or(vec![Filter::and(vec![])]).to_document(), Document::new());
+    }
+
+    #[test]
+    fn or_without_members_matches_nothing() {
+        assert_eq!(Filter::or(vec![]).to_document(), doc! { "_id": { "$in": [] } });
+    }
+
+    #[test]
+    fn or_compiles_members() {
+        let filter = Filter::or(vec![Filter::eq("x", 1), Filter::gte("y", 2)]);
+        assert_eq!(filter.to_document(), doc! { "$or": [{ "x": 1 }, { "y": { "$gte": 2 } }] });
+        assert_eq!(Filter::or(vec![Filter::eq("x", 1)]).to_document(), doc! { "x": 1 });
+    }
+
+    #[test]
+    fn invalid_object_id_matches_nothing() {
+        assert_eq!(Filter::object_id("parent_id", "invalid").to_document(), doc! { "parent_id": { "$in": [] } });
+    }
+}
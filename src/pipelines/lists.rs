@@ -0,0 +1,372 @@
+use actix_web::{Error, FromRequest, HttpRequest, Result};
+use actix_web::dev::Payload as RequestPayload;
+use actix_web::web::{Data, Query};
+use chrono::DateTime;
+use futures::future::{err, ok, Ready};
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::pipelines::{MatchMode, Pipeline, PAGE_PARAM, PER_PAGE_PARAM};
+use crate::pipelines::filters::MAX_PATTERN_LENGTH;
+use crate::Message;
+use crate::Payload;
+
+// Reserved query parameters
+pub static SORT_PARAM: &str = "sort";
+pub static SEARCH_PARAM: &str = "search";
+
+// Type of a filterable field, query values are coerced to it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ListFieldType {
+    #[default]
+    String,
+    Int,
+    Double,
+    Bool,
+    Date,
+    ObjectId,
+}
+
+impl ListFieldType {
+    // Coerces query value into the field type
+    fn to_bson(self, value: &str) -> std::result::Result<Bson, Message> {
+        match self {
+            Self::String => Ok(Bson::String(value.to_string())),
+            Self::Int => value
+                .parse::<i64>()
+                .map(Bson::Int64)
+                .map_err(|_| Message::new("list.expected_integer")),
+            Self::Double => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(Bson::Double(number)),
+                _ => Err(Message::new("list.expected_number"))
+            },
+            Self::Bool => match value {
+                "true" | "1" => Ok(Bson::Boolean(true)),
+                "false" | "0" => Ok(Bson::Boolean(false)),
+                _ => Err(Message::new("list.expected_boolean"))
+            },
+            Self::Date => DateTime::parse_from_rfc3339(value)
+                .map(|date| Bson::DateTime(mongodb::bson::DateTime::from_millis(date.timestamp_millis())))
+                .map_err(|_| Message::new("list.expected_date")),
+            Self::ObjectId => ObjectId::from_str(value)
+                .map(Bson::ObjectId)
+                .map_err(|_| Message::new("list.invalid_id")),
+        }
+    }
+}
+
+// Filter operators accepted as `field[op]=value`, plain `field=value` is `Eq`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListOperator {
+    Eq,
+    In,
+    Gte,
+    Lte,
+    Regex,
+    Exists,
+}
+
+impl FromStr for ListOperator {
+    type Err = Message;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "eq" => Ok(Self::Eq),
+            "in" => Ok(Self::In),
+            "gte" => Ok(Self::Gte),
+            "lte" => Ok(Self::Lte),
+            "regex" => Ok(Self::Regex),
+            "exists" => Ok(Self::Exists),
+            _ => Err(Message::new("list.unknown_operator").set_arg("operator", value))
+        }
+    }
+}
+
+// Create compiled filter
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListFilter {
+    pub field: String,
+    pub operator: ListOperator,
+    pub value: Bson,
+}
+
+impl ListFilter {
+    pub fn to_document(&self) -> Document {
+        let field = self.field.clone();
+
+        match self.operator {
+            ListOperator::Eq => doc! { field: self.value.clone() },
+            ListOperator::In => doc! { field: { "$in": self.value.clone() } },
+            ListOperator::Gte => doc! { field: { "$gte": self.value.clone() } },
+            ListOperator::Lte => doc! { field: { "$lte": self.value.clone() } },
+            ListOperator::Regex => doc! { field: { "$regex": self.value.clone(), "$options": "i" } },
+            ListOperator::Exists => doc! { field: { "$exists": self.value.clone() } },
+        }
+    }
+}
+
+/// List query options is the whitelist used by `ListQuery`. Register it as app data on the
+/// resource (`.app_data(ListQueryOptions::builder()...)` or wrapped in `Data`)
+#[derive(Debug, Clone)]
+pub struct ListQueryOptions {
+    pub filterable: Vec<(String, ListFieldType, Vec<ListOperator>)>,
+    pub sortable: Vec<String>,
+    pub searchable: Vec<String>,
+    pub default_sort: Vec<(String, i32)>,
+    pub default_per_page: i32,
+    pub max_per_page: i32,
+}
+
+impl Default for ListQueryOptions {
+    fn default() -> Self {
+        Self {
+            filterable: Vec::new(),
+            sortable: Vec::new(),
+            searchable: Vec::new(),
+            default_sort: Vec::new(),
+            default_per_page: 10,
+            max_per_page: 100,
+        }
+    }
+}
+
+impl ListQueryOptions {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    // Values of the field are coerced to `field_type`, `Regex` is only allowed on strings
+    pub fn set_filterable<T>(&self, field: T, field_type: ListFieldType, operators: &[ListOperator]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.filterable.retain(|(f, _, _)| *f != field.to_string());
+        data.filterable.push((field.to_string(), field_type, operators.to_vec()));
+        data
+    }
+
+    pub fn set_sortable<T>(&self, fields: &[T]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.sortable = fields.iter().map(|f| f.to_string()).collect();
+        data
+    }
+
+    pub fn set_searchable<T>(&self, fields: &[T]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.searchable = fields.iter().map(|f| f.to_string()).collect();
+        data
+    }
+
+    pub fn set_default_sort<T>(&self, field: T, order: i32) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.default_sort = vec![(field.to_string(), order)];
+        data
+    }
+
+    pub fn set_per_page(&self, default_per_page: i32, max_per_page: i32) -> Self {
+        let mut data = self.clone();
+        data.default_per_page = default_per_page.max(1);
+        data.max_per_page = max_per_page.max(data.default_per_page);
+        data
+    }
+}
+
+/// List query parses `?page=&per_page=&sort=-created_at,name&search=&field[op]=value`
+/// against `ListQueryOptions`, unknown fields or operators are rejected with a 400 payload
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ListQuery {
+    pub page: i32,
+    pub per_page: i32,
+    pub sort: Vec<(String, i32)>,
+    pub search: Option<String>,
+    pub searchable: Vec<String>,
+    pub filters: Vec<ListFilter>,
+}
+
+impl ListQuery {
+    pub fn parse<T>(query_string: T, options: &ListQueryOptions) -> Result<Self>
+        where T: ToString
+    {
+        let pairs = match Query::<Vec<(String, String)>>::from_query(&query_string.to_string()) {
+            Ok(pairs) => pairs.into_inner(),
+            Err(error) => return Err(Payload::error(error))
+        };
+
+        let mut data = Self {
+            page: 1,
+            per_page: options.default_per_page,
+            sort: options.default_sort.clone(),
+            searchable: options.searchable.clone(),
+            ..Default::default()
+        };
+
+        let mut errors = BTreeMap::new();
+
+        for (key, value) in pairs {
+            let value = value.trim().to_string();
+
+            match key.as_str() {
+                key if key == PAGE_PARAM => match value.parse::<i32>() {
+                    Ok(page) if page >= 1 => data.page = page,
+                    _ => { errors.insert(key.to_string(), Message::new("list.invalid").to_string()); }
+                },
+                key if key == PER_PAGE_PARAM => match value.parse::<i32>() {
+                    Ok(per_page) if per_page >= 1 => data.per_page = per_page.min(options.max_per_page),
+                    _ => { errors.insert(key.to_string(), Message::new("list.invalid").to_string()); }
+                },
+                key if key == SORT_PARAM => {
+                    let mut sort = Vec::new();
+
+                    for field in value.split(',').map(|f| f.trim()).filter(|f| !f.is_empty()) {
+                        let (field, order) = match field.strip_prefix('-') {
+                            Some(field) => (field, -1),
+                            None => (field.trim_start_matches('+'), 1)
+                        };
+
+                        match options.sortable.iter().any(|f| f == field) {
+                            true => sort.push((field.to_string(), order)),
+                            false => { errors.insert(key.to_string(), Message::new("list.not_sortable").set_arg("field", field).to_string()); }
+                        }
+                    }
+
+                    if !sort.is_empty() {
+                        data.sort = sort;
+                    }
+                },
+                key if key == SEARCH_PARAM => match options.searchable.is_empty() {
+                    true => { errors.insert(key.to_string(), Message::new("list.search_not_allowed").to_string()); },
+                    false => match MatchMode::Contains.try_to_pattern(&value) {
+                        Ok(_) => data.search = Some(value).filter(|v| !v.is_empty()),
+                        Err(_) => { errors.insert(key.to_string(), Message::new("list.maximum").set_arg("max", MAX_PATTERN_LENGTH).to_string()); }
+                    }
+                },
+                _ => match parse_filter(&key, &value, options) {
+                    Ok(filter) => data.filters.push(filter),
+                    Err(error) => { errors.insert(key.clone(), error.to_string()); }
+                }
+            }
+        }
+
+        // The skip of `table_facet` has to fit the server's 32 bit `$skip`
+        if (data.page as i64 - 1) * data.per_page as i64 > i32::MAX as i64 {
+            errors.insert(PAGE_PARAM.to_string(), Message::new("list.invalid").to_string());
+        }
+
+        match errors.is_empty() {
+            true => Ok(data),
+            false => Err(Payload::errors(errors))
+        }
+    }
+
+    /// Appends match, search and sort stages to the pipeline
+    pub fn apply<'a>(&self, pipeline: &'a mut Pipeline) -> &'a mut Pipeline {
+        let array = self.filters
+            .iter()
+            .map(|filter| filter.to_document())
+            .collect::<Vec<Document>>();
+
+        if !array.is_empty() {
+            pipeline.custom(doc! { "$match": { "$and": array } });
+        }
+
+        // Search is escaped so it always matches literally
+        if let Some(search) = self.search.clone() {
+            let search = self.searchable
+                .iter()
+                .map(|field| doc! { field: { "$regex": MatchMode::Contains.to_pattern(&search), "$options": "i" } })
+                .collect::<Vec<Document>>();
+
+            pipeline.global_search(&search);
+        }
+
+        if !self.sort.is_empty() {
+            let mut sort = Document::new();
+            for (field, order) in &self.sort {
+                sort.insert(field, order);
+            }
+
+            pipeline.custom(doc! { "$sort": sort });
+        }
+
+        pipeline
+    }
+
+    /// Builds pipeline with filters, sort and `table_facet`/`table_pagination`
+    pub fn to_pipeline(&self) -> Pipeline {
+        let mut pipeline = Pipeline::builder();
+
+        self.apply(&mut pipeline)
+            .table_facet(&self.page, &self.per_page)
+            .table_pagination(&self.page, &self.per_page);
+
+        pipeline
+    }
+}
+
+// Implement from request
+impl FromRequest for ListQuery {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut RequestPayload) -> Self::Future {
+        let options = req
+            .app_data::<ListQueryOptions>()
+            .cloned()
+            .or_else(|| req.app_data::<Data<ListQueryOptions>>().map(|o| o.get_ref().clone()))
+            .unwrap_or_default();
+
+        match Self::parse(req.query_string(), &options) {
+            Ok(query) => ok(query),
+            Err(error) => err(error)
+        }
+    }
+}
+
+// Parses `field=value` or `field[op]=value` against the whitelist
+fn parse_filter(key: &str, value: &str, options: &ListQueryOptions) -> std::result::Result<ListFilter, Message> {
+    let (field, operator) = match key.split_once('[') {
+        Some((field, rest)) => match rest.strip_suffix(']') {
+            Some(operator) => (field, ListOperator::from_str(operator)?),
+            None => return Err(Message::new("list.invalid_filter"))
+        },
+        None => (key, ListOperator::Eq)
+    };
+
+    let (field_type, operators) = match options.filterable.iter().find(|(f, _, _)| f == field) {
+        Some((_, field_type, operators)) => (*field_type, operators),
+        None => return Err(Message::new("list.not_filterable").set_arg("field", field))
+    };
+
+    if !operators.contains(&operator) {
+        return Err(Message::new("list.operator_not_allowed")
+            .set_arg("operator", format!("{operator:?}").to_lowercase())
+            .set_arg("field", field));
+    }
+
+    let value = match operator {
+        ListOperator::In => Bson::Array(value
+            .split(',')
+            .map(|v| v.trim())
+            .filter(|v| !v.is_empty())
+            .map(|v| field_type.to_bson(v))
+            .collect::<std::result::Result<Vec<Bson>, Message>>()?),
+        ListOperator::Exists => ListFieldType::Bool.to_bson(value)?,
+        ListOperator::Regex => match field_type {
+            ListFieldType::String => match MatchMode::Contains.try_to_pattern(value) {
+                Ok(pattern) => Bson::String(pattern),
+                Err(_) => return Err(Message::new("list.maximum").set_arg("max", MAX_PATTERN_LENGTH))
+            },
+            _ => return Err(Message::new("list.operator_not_allowed").set_arg("operator", "regex").set_arg("field", field))
+        },
+        _ => field_type.to_bson(value)?
+    };
+
+    Ok(ListFilter { field: field.to_string(), operator, value })
+}
@@ -1,15 +1,39 @@
 use actix_web::Result;
-use mongodb::{bson::{doc, from_document, Bson, oid::ObjectId, Document, Regex}, options::AggregateOptions, Collection};
+use mongodb::{bson::{doc, from_document, Bson, oid::ObjectId, Document, Regex}, options::{AggregateOptions, IndexOptions}, Collection, IndexModel};
 use serde::de::DeserializeOwned;
+use std::fmt;
 use std::str::FromStr;
-use futures::{StreamExt, TryStreamExt};
+use std::time::{Duration, Instant};
+use futures::{stream::LocalBoxStream, StreamExt, TryStreamExt};
 
 use crate::traits::*;
+use cursors::cursor_fields;
+use crate::Message;
 use crate::Payload;
 
+pub mod cursors;
+pub mod dates;
+pub mod explains;
+pub mod filters;
+pub mod lists;
+pub mod pages;
+pub mod stages;
+pub mod visibility;
+
+pub use cursors::{CursorPage, PageCursor};
+pub use dates::TimeUnit;
+pub use explains::Explain;
+pub use filters::{Filter, MatchMode};
+pub use lists::{ListFieldType, ListFilter, ListOperator, ListQuery, ListQueryOptions};
+pub use pages::{Page, PageLinks, Pagination, PAGE_PARAM, PER_PAGE_PARAM};
+pub use stages::{Accumulator, WhenMatched, WhenNotMatched};
+pub use visibility::{Visibility, VisibilityClaims};
+
 #[derive(Debug, Default, Clone)]
 pub struct Pipeline {
-    queries: Vec<Document>
+    queries: Vec<Document>,
+    is_strict: bool,
+    slow_query: Option<Duration>
 }
 
 impl Pipeline {
@@ -21,81 +45,291 @@ impl Pipeline {
         self.queries.clone()
     }
 
-    pub async fn aggregate_result_many<T>(&self,  collection: &Collection<T>) -> Result<Vec<T>>
-        where T: IsEmpty + DeserializeOwned + ToJson + Default
-    {
-        let  cursor = match collection.clone().aggregate(self.queries.to_owned(), AggregateOptions::builder().build()).await {
+    /// Fails on documents that can't be deserialized (or lack a requested field) instead of
+    /// replacing them with defaults, the error names the document `_id`
+    pub fn strict(&mut self, is_strict: bool) -> &mut Self {
+        self.is_strict = is_strict;
+
+        self
+    }
+
+    /// Logs aggregations of this pipeline that take longer than `threshold_ms`
+    pub fn slow_query(&mut self, threshold_ms: u64) -> &mut Self {
+        self.slow_query = Some(Duration::from_millis(threshold_ms));
+
+        self
+    }
+
+    /// Returns `build()` as pretty relaxed extended json, handy for debugging or pasting into a shell
+    pub fn dump(&self) -> String {
+        let queries = Bson::Array(self.queries.iter().cloned().map(Bson::Document).collect());
+
+        serde_json::to_string_pretty(&queries.into_relaxed_extjson()).unwrap_or_default()
+    }
+
+    /// Runs the pipeline with `executionStats` verbosity and returns the parsed plan
+    pub async fn explain<T>(&self, collection: &Collection<T>) -> Result<Explain> {
+        let namespace = collection.namespace();
+        let command = doc! {
+            "explain": {
+                "aggregate": namespace.coll.clone(),
+                "pipeline": self.queries.to_owned(),
+                "cursor": {}
+            },
+            "verbosity": "executionStats"
+        };
+
+        match collection.client().database(&namespace.db).run_command(command, None).await {
+            Ok(result) => Ok(Explain::from_document(result)),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+
+    // Runs aggregation and collects every document, logging it when slow
+    async fn collect_documents<T>(&self, collection: &Collection<T>, queries: Vec<Document>) -> Result<Vec<Document>> {
+        let started = Instant::now();
+        let cursor = match collection.clone().aggregate(queries, AggregateOptions::builder().build()).await {
             Ok(cursor) => cursor,
             Err(error) => return Err(Payload::error(error))
         };
 
-        let data = cursor.map_ok(|value| {
-            from_document::<T>(value).unwrap_or_default().to_json().unwrap_or_default()
-        }).try_collect::<Vec<T>>().await;
+        let documents = match cursor.try_collect::<Vec<Document>>().await {
+            Ok(documents) => documents,
+            Err(error) => return Err(Payload::error(error))
+        };
 
-        match data {
-            Ok(data) => Ok(data),
-            Err(error) => Err(Payload::error(error))
-        }
+        self.log_slow_query(collection, started);
+
+        Ok(documents)
     }
 
-    pub async fn aggregate_result_many_string<T, U>(&self,  collection: &Collection<T>, key: U) -> Result<Vec<String>>
-        where T: IsEmpty + DeserializeOwned + ToJson + Default,
-              U: ToString
-    {
-        let  cursor = match collection.clone().aggregate(self.queries.to_owned(), AggregateOptions::builder().build()).await {
+    // Runs aggregation and returns its first document, logging it when slow
+    async fn next_document<T>(&self, collection: &Collection<T>) -> Result<Option<Document>> {
+        let started = Instant::now();
+        let mut cursor = match collection.clone().aggregate(self.queries.to_owned(), None).await {
             Ok(cursor) => cursor,
             Err(error) => return Err(Payload::error(error))
         };
 
-        let data = cursor.map_ok(|value| {
-            value.get(&key.to_string()).and_then(Bson::as_str).unwrap_or_default().to_owned()
-        }).try_collect::<Vec<String>>().await;
+        let document = match cursor.next().await {
+            Some(Ok(document)) => Some(document),
+            Some(Err(error)) => return Err(Payload::error(error)),
+            None => None
+        };
 
-        match data {
-            Ok(data) => Ok(data),
-            Err(error) => Err(Payload::error(error))
+        self.log_slow_query(collection, started);
+
+        Ok(document)
+    }
+
+    fn log_slow_query<T>(&self, collection: &Collection<T>, started: Instant) {
+        let elapsed = started.elapsed();
+
+        if let Some(threshold) = self.slow_query.filter(|threshold| elapsed > *threshold) {
+            println!(
+                "Slow query on {} took {}ms (threshold {}ms): {}",
+                collection.namespace(),
+                elapsed.as_millis(),
+                threshold.as_millis(),
+                serde_json::to_string(&Bson::Array(self.queries.iter().cloned().map(Bson::Document).collect()).into_relaxed_extjson()).unwrap_or_default()
+            );
         }
     }
 
+    pub async fn aggregate_result_many<T>(&self,  collection: &Collection<T>) -> Result<Vec<T>>
+        where T: IsEmpty + DeserializeOwned + ToJson + Default
+    {
+        let documents = self.collect_documents(collection, self.queries.to_owned()).await?;
+
+        let mut data = Vec::new();
+
+        for document in documents {
+            data.push(self.read_document(document)?);
+        }
+
+        Ok(data)
+    }
+
+    // Deserializes document, strict pipelines fail with its `_id` instead of using the default
+    fn read_document<T>(&self, document: Document) -> Result<T>
+        where T: DeserializeOwned + ToJson + Default
+    {
+        let id = get_document_id(&document);
+
+        match from_document::<T>(document) {
+            Ok(value) => Ok(value.to_json().unwrap_or_default()),
+            Err(error) => match self.is_strict {
+                true => Err(Payload::error(Message::new("request.unreadable_document").set_arg("id", &id).set_arg("error", error))),
+                false => Ok(T::default())
+            }
+        }
+    }
+
+    /// Returns a single field of every document, e.g. `aggregate_result_many_field::<_, _, String>(.., "name")`
+    pub async fn aggregate_result_many_field<T, U, V>(&self,  collection: &Collection<T>, key: U) -> Result<Vec<V>>
+        where U: ToString,
+              V: FromBson + Default
+    {
+        let key = key.to_string();
+        let documents = self.collect_documents(collection, self.queries.to_owned()).await?;
+
+        let mut data = Vec::new();
+
+        for document in documents {
+            match (document.get(&key).and_then(V::from_bson), self.is_strict) {
+                (Some(value), _) => data.push(value),
+                (None, false) => data.push(V::default()),
+                (None, true) => return Err(Payload::error(Message::new("request.unreadable_field").set_arg("field", &key).set_arg("id", get_document_id(&document))))
+            }
+        }
+
+        Ok(data)
+    }
+
+    pub async fn aggregate_result_many_string<T, U>(&self,  collection: &Collection<T>, key: U) -> Result<Vec<String>>
+        where T: IsEmpty + DeserializeOwned + ToJson + Default,
+              U: ToString
+    {
+        self.aggregate_result_many_field::<T, U, String>(collection, key).await
+    }
+
     pub async fn aggregate_result_many_i64<T, U>(&self,  collection: &Collection<T>, key: U) -> Result<Vec<i64>>
         where T: IsEmpty + DeserializeOwned + ToJson + Default,
               U: ToString
     {
-        let  cursor = match collection.clone().aggregate(self.queries.to_owned(), AggregateOptions::builder().build()).await {
+        self.aggregate_result_many_field::<T, U, i64>(collection, key).await
+    }
+
+    /// Streams results instead of collecting them, documents that can't be deserialized are
+    /// yielded as errors naming their `_id`
+    pub async fn stream<T>(&self, collection: &Collection<T>) -> Result<LocalBoxStream<'static, Result<T>>>
+        where T: DeserializeOwned + 'static
+    {
+        let cursor = match collection.clone().aggregate(self.queries.to_owned(), None).await {
             Ok(cursor) => cursor,
             Err(error) => return Err(Payload::error(error))
         };
 
-        let data = cursor.map_ok(|value| {
-            value.get(&key.to_string()).and_then(Bson::as_i64).unwrap_or_default().to_owned()
-        }).try_collect::<Vec<i64>>().await;
+        let stream = cursor.map(|value| match value {
+            Ok(document) => {
+                let id = get_document_id(&document);
 
-        match data {
-            Ok(data) => Ok(data),
+                from_document::<T>(document)
+                    .map_err(|error| Payload::error(Message::new("request.unreadable_document").set_arg("id", &id).set_arg("error", error)))
+            },
             Err(error) => Err(Payload::error(error))
-        }
+        });
+
+        Ok(stream.boxed_local())
     }
 
     pub async fn aggregate_result_one<T>(&self,  collection: &Collection<T>) -> Result<T>
         where T: IsEmpty + DeserializeOwned,
     {
-        let mut cursor = match collection.clone().aggregate(self.queries.to_owned(), None).await {
-            Ok(cursor) => cursor,
-            Err(error) => return Err(Payload::error(error))
+        if let Some(value) = self.next_document(collection).await? {
+            match from_document::<T>(value) {
+                Ok(data) => if !data.is_empty() { return Ok(data); },
+                Err(error) => return Err(Payload::error(error))
+            }
+        }
+
+        Err(Payload::error(Message::new("request.record_not_found")))
+    }
+
+    /// Runs a pipeline ending with `table_facet` and `table_pagination` and returns the page
+    pub async fn aggregate_page<T>(&self, collection: &Collection<T>, current_page: &i32, per_page: &i32) -> Result<Page<T>>
+        where T: IsEmpty + DeserializeOwned + ToJson + Default + Clone
+    {
+        match self.next_document(collection).await? {
+            Some(value) => match from_document::<Page<T>>(value) {
+                Ok(page) => Ok(page.to_json()),
+                Err(error) => Err(Payload::error(error))
+            },
+            None => Ok(Page::empty(*current_page as i64, *per_page as i64))
+        }
+    }
+
+    /// Runs a pipeline built with `after_cursor` and returns up to `limit` records along with
+    /// the cursor of the next page. One extra record is fetched to know if there are more
+    pub async fn aggregate_cursor_page<T, U>(&self, collection: &Collection<T>, sort_fields: &[(U, i32)], limit: usize) -> Result<CursorPage<T>>
+        where T: IsEmpty + DeserializeOwned + ToJson + Default,
+              U: ToString
+    {
+        let mut queries = self.queries.to_owned();
+        queries.push(doc! { "$limit": (limit + 1) as i64 });
+
+        let mut documents = self.collect_documents(collection, queries).await?;
+
+        let has_more = documents.len() > limit;
+        documents.truncate(limit);
+
+        let next_cursor = match has_more {
+            true => documents.last().map(|document| PageCursor::from_document(document, sort_fields).encode()),
+            false => None
         };
 
-        if let Some(value) = cursor.next().await {
-            match value {
-                Ok(value) => match from_document::<T>(value) {
-                    Ok(data) => if !data.is_empty() { return Ok(data); },
-                    Err(error) => return Err(Payload::error(error))
-                },
-                Err(error) => return Err(Payload::error(error))
+        let list = documents
+            .into_iter()
+            .map(|document| self.read_document(document))
+            .collect::<Result<Vec<T>>>()?;
+
+        Ok(CursorPage { list, next_cursor, has_more })
+    }
+
+    /// Keyset pagination, matches records after the cursor and sorts by `sort_fields` then `_id`.
+    /// Pass None for the first page, a cursor built for other sort fields is rejected. Null and
+    /// missing values sort first like in MongoDB. Sort fields should be indexed together with `_id`
+    pub fn after_cursor<T>(&mut self, sort_fields: &[(T, i32)], cursor: Option<&PageCursor>) -> Result<&mut Self>
+        where T: ToString
+    {
+        let fields = cursor_fields(sort_fields);
+
+        if let Some(cursor) = cursor {
+            if cursor.values.len() != fields.len() {
+                return Err(Payload::error(Message::new("request.invalid_cursor")));
             }
+
+            // (a > x) or (a = x and b > y) or ...
+            let branches = (0..fields.len())
+                .filter_map(|index| {
+                    let mut branch = Document::new();
+
+                    for (position, (field, _)) in fields.iter().enumerate().take(index) {
+                        branch.insert(field, doc! { "$eq": cursor.values[position].clone() });
+                    }
+
+                    let (field, order) = &fields[index];
+                    let value = cursor.values[index].clone();
+
+                    match (*order < 0, value) {
+                        // Nothing sorts before null
+                        (true, Bson::Null) => return None,
+                        (false, Bson::Null) => { branch.insert(field, doc! { "$ne": Bson::Null }); },
+                        (false, value) => { branch.insert(field, doc! { "$gt": value }); },
+                        // Nulls sort last when descending but `$lt` never matches them
+                        (true, value) => { branch.insert("$or", vec![
+                            doc! { field.clone(): { "$lt": value } },
+                            doc! { field.clone(): { "$eq": Bson::Null } },
+                        ]); }
+                    }
+
+                    Some(Bson::Document(branch))
+                })
+                .collect::<Vec<Bson>>();
+
+            self.queries.push(doc! {
+                "$match": { "$or": branches }
+            });
+        }
+
+        let mut sort = Document::new();
+        for (field, order) in fields {
+            sort.insert(field, order);
         }
 
-        Err(Payload::error("No matching record was found in the database. Please check your input and try again"))
+        self.queries.push(doc! { "$sort": sort });
+
+        Ok(self)
     }
 
     pub fn custom(&mut self, value: Document) -> &mut Self {
@@ -104,6 +338,19 @@ impl Pipeline {
         self
     }
 
+    /// Adds `$match` stage for a typed filter expression
+    pub fn filter(&mut self, filter: Filter) -> &mut Self {
+        let filter = filter.to_document();
+
+        if !filter.is_empty() {
+            self.queries.push(doc! {
+                "$match": filter
+            });
+        }
+
+        self
+    }
+
     pub fn filters(&mut self, value: &[Document]) -> &mut Self {
         let value = value.to_owned();
 
@@ -126,6 +373,63 @@ impl Pipeline {
         self
     }
 
+    /// Atlas Search on the given index, results come back by relevance and the score is
+    /// stored in `search_score`. Must be the first stage of the pipeline
+    pub fn atlas_search<I, T, P>(&mut self, index: I, term: T, paths: &[P]) -> &mut Self
+        where I: ToString,
+              T: ToString,
+              P: ToString
+    {
+        let term = term.to_string();
+        if term.trim().is_empty() {
+            return self;
+        }
+
+        self.queries.push(doc! {
+            "$search": {
+                "index": index.to_string(),
+                "text": {
+                    "query": term.trim(),
+                    "path": paths.iter().map(|p| p.to_string()).collect::<Vec<String>>()
+                }
+            }
+        });
+
+        self.queries.push(doc! {
+            "$addFields": { "search_score": { "$meta": "searchScore" } }
+        });
+
+        self
+    }
+
+    /// Builds text index model for `fields` as (field, weight), see `create_text_index`
+    pub fn text_index<T>(fields: &[(T, i32)]) -> IndexModel
+        where T: ToString
+    {
+        let mut keys = Document::new();
+        let mut weights = Document::new();
+
+        for (field, weight) in fields {
+            keys.insert(field.to_string(), "text");
+            weights.insert(field.to_string(), (*weight).max(1));
+        }
+
+        IndexModel::builder()
+            .keys(keys)
+            .options(IndexOptions::builder().weights(weights).build())
+            .build()
+    }
+
+    /// Creates the text index `text_search` needs, a collection can only have one
+    pub async fn create_text_index<C, T>(collection: &Collection<C>, fields: &[(T, i32)]) -> Result<String>
+        where T: ToString
+    {
+        match collection.create_index(Self::text_index(fields), None).await {
+            Ok(result) => Ok(result.index_name),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+
     pub fn graph_lookup<T, U, V, W>(
         &mut self,
         from: T,
@@ -180,9 +484,19 @@ impl Pipeline {
         self
     }
 
+    // Always matches `name` and `parents.name` by regex, use `filter` with `Filter::from_tuples`
+    // to choose the regex fields
     pub fn match_and<T, U>(&mut self, fields: Vec<(T, U, bool)>, status: Option<Vec<String>>) -> &mut Self
         where T: ToString,
               U: ToString
+    {
+        self.match_and_mode(fields, status, MatchMode::Contains)
+    }
+
+    /// Same as `match_and` with an explicit match mode for `name` and `parents.name`
+    pub fn match_and_mode<T, U>(&mut self, fields: Vec<(T, U, bool)>, status: Option<Vec<String>>, mode: MatchMode) -> &mut Self
+        where T: ToString,
+              U: ToString
     {
         let mut array = Vec::new();
 
@@ -201,7 +515,7 @@ impl Pipeline {
                 },
                 false => match value.trim().is_empty() {
                     false => match field.as_str() == "name" || field.as_str() == "parents.name" {
-                        true => array.push(doc!{ field.clone(): { "$regex": value.to_lowercase().to_string(), "$options": "i" } }),
+                        true => array.push(doc!{ field.clone(): { "$regex": mode.to_pattern(&value), "$options": "i" } }),
                         false => array.push(doc!{ field.clone(): value.to_string() })
                     },
                     true => array.push(doc!{ field.clone(): { "$exists": false } })
@@ -227,6 +541,14 @@ impl Pipeline {
     pub fn match_and_in<T, U>(&mut self, field:T, values: Vec<U>, is_object_id: bool, status: Option<Vec<String>>) -> &mut Self
         where T: ToString,
               U: ToString
+    {
+        self.match_and_in_mode(field, values, is_object_id, status, MatchMode::Contains)
+    }
+
+    /// Same as `match_and_in` with an explicit match mode for non object id values
+    pub fn match_and_in_mode<T, U>(&mut self, field:T, values: Vec<U>, is_object_id: bool, status: Option<Vec<String>>, mode: MatchMode) -> &mut Self
+        where T: ToString,
+              U: ToString
     {
         let field = field.to_string();
         let mut array = Vec::new();
@@ -251,9 +573,7 @@ impl Pipeline {
                 let mut items = Vec::new();
 
                 for i in values {
-                    let item = i.to_string().trim().to_string();
-
-                    items.push(Regex{ pattern: item, options: "i".to_string() })
+                    items.push(Regex{ pattern: mode.to_pattern(i), options: "i".to_string() })
                 }
 
                 if !items.is_empty() {
@@ -390,32 +710,29 @@ impl Pipeline {
         self
     }
 
+    // Matches records with the given role, no role is excluded anymore. Apply
+    // `Visibility::builder().set_hidden_roles(&["Controller"])` with `visibility` for the old exclusion
     pub fn role<T>(&mut self, role: &Option<T>) -> &mut Self
         where T: ToString
     {
-        match role {
-            Some(role) => {
-                self.queries.push(doc! {
-                    "$match": {
-                        "$and": [
-                            { "role": { "$eq": role.to_string() } },
-                            { "role": { "$ne": "Controller" } }
-                        ]
-                    }
-                });
-            },
-            None => {
-                self.queries.push(doc! {
-                    "$match": {
-                        "role": { "$ne": "Controller" }
-                    }
-                });
-            }
+        if let Some(role) = role {
+            self.queries.push(doc! {
+                "$match": {
+                    "role": { "$eq": role.to_string() }
+                }
+            });
         }
 
         self
     }
 
+    /// Applies visibility policy for the caller claims resolved by `PermissionGuard`
+    pub fn visibility<C>(&mut self, policy: &Visibility, claims: Option<&C>) -> &mut Self
+        where C: VisibilityClaims
+    {
+        self.filter(policy.to_filter(claims))
+    }
+
     pub fn sort<T>(&mut self, field: T, order: i32) -> &mut Self
         where T: ToString
     {
@@ -442,11 +759,36 @@ impl Pipeline {
         self
     }
 
+    /// Full text search using the collection text index, sorted by relevance with the score
+    /// stored in `text_score`. Must be the first stage of the pipeline
+    pub fn text_search<T>(&mut self, term: T) -> &mut Self
+        where T: ToString
+    {
+        let term = term.to_string();
+        if term.trim().is_empty() {
+            return self;
+        }
+
+        self.queries.push(doc! {
+            "$match": { "$text": { "$search": term.trim() } }
+        });
+
+        self.queries.push(doc! {
+            "$addFields": { "text_score": { "$meta": "textScore" } }
+        });
+
+        self.queries.push(doc! {
+            "$sort": { "text_score": { "$meta": "textScore" } }
+        });
+
+        self
+    }
+
     pub fn table_facet(&mut self, current_page: &i32, per_page: &i32) -> &mut Self {
         self.queries.push(doc!{
             "$facet": {
                 "list":[
-                    { "$skip": (*current_page - 1) * *per_page },
+                    { "$skip": (*current_page as i64 - 1).max(0) * *per_page as i64 },
                     { "$limit": *per_page }
                 ],
                 "count": [ { "$count": "total" } ]
@@ -471,3 +813,19 @@ impl Pipeline {
         self
     }
 }
+
+// Retrieves document id for error messages
+fn get_document_id(document: &Document) -> String {
+    match document.get("_id") {
+        Some(Bson::ObjectId(id)) => id.to_hex(),
+        Some(id) => id.to_string(),
+        None => String::from("without _id")
+    }
+}
+
+// Implement display for pipeline
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.dump())
+    }
+}
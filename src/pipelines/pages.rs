@@ -0,0 +1,128 @@
+use actix_web::HttpRequest;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::traits::ToJson;
+
+// Query parameters used when building pagination links
+pub static PAGE_PARAM: &str = "page";
+pub static PER_PAGE_PARAM: &str = "per_page";
+
+/// Page of results produced by `Pipeline::table_facet` followed by `Pipeline::table_pagination`.
+/// Counts are missing from that document when nothing matched, so they default to 0
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    #[serde(default = "Vec::new")]
+    pub list: Vec<T>,
+    #[serde(default, deserialize_with = "null_default")]
+    pub total: i64,
+    #[serde(default, deserialize_with = "null_default")]
+    pub current_page: i64,
+    #[serde(default, deserialize_with = "null_default")]
+    pub per_page: i64,
+    #[serde(default, deserialize_with = "null_default")]
+    pub pages: i64,
+    #[serde(default, deserialize_with = "null_default")]
+    pub total_results: i64,
+}
+
+// Create pagination links
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PageLinks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+}
+
+// Create pagination metadata sent along `Payload::page`
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pagination {
+    pub total: i64,
+    pub current_page: i64,
+    pub per_page: i64,
+    pub pages: i64,
+    pub links: PageLinks,
+}
+
+impl<T> Page<T> {
+    /// Creates empty page, used when the aggregation returned no document
+    pub fn empty(current_page: i64, per_page: i64) -> Self {
+        Self {
+            list: Vec::new(),
+            total: 0,
+            current_page,
+            per_page,
+            pages: 0,
+            total_results: 0,
+        }
+    }
+
+    pub fn has_next(&self) -> bool {
+        self.current_page < self.pages
+    }
+
+    pub fn has_prev(&self) -> bool {
+        self.current_page > 1 && self.pages > 0
+    }
+
+    pub fn map<U, F>(self, f: F) -> Page<U>
+        where F: FnMut(T) -> U
+    {
+        Page {
+            list: self.list.into_iter().map(f).collect(),
+            total: self.total,
+            current_page: self.current_page,
+            per_page: self.per_page,
+            pages: self.pages,
+            total_results: self.total_results,
+        }
+    }
+
+    /// Returns pagination metadata with next/prev links relative to the request url
+    pub fn pagination(&self, req: &HttpRequest) -> Pagination {
+        let link = |page: i64| {
+            let mut query = req
+                .query_string()
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .filter(|pair| {
+                    let key = pair.split('=').next().unwrap_or_default();
+                    key != PAGE_PARAM && key != PER_PAGE_PARAM
+                })
+                .map(String::from)
+                .collect::<Vec<String>>();
+
+            query.push(format!("{PAGE_PARAM}={page}"));
+            query.push(format!("{PER_PAGE_PARAM}={}", self.per_page));
+
+            format!("{}?{}", req.path(), query.join("&"))
+        };
+
+        Pagination {
+            total: self.total,
+            current_page: self.current_page,
+            per_page: self.per_page,
+            pages: self.pages,
+            links: PageLinks {
+                next: self.has_next().then(|| link(self.current_page + 1)),
+                prev: self.has_prev().then(|| link((self.current_page - 1).min(self.pages))),
+            },
+        }
+    }
+}
+
+impl<T> Page<T>
+    where T: ToJson + Default + Clone
+{
+    pub fn to_json(&self) -> Self {
+        self.clone().map(|item| item.to_json().unwrap_or_default())
+    }
+}
+
+// Deserializes null values (e.g. `total` of an empty facet) as default
+fn null_default<'de, D, V>(deserializer: D) -> Result<V, D::Error>
+    where D: Deserializer<'de>,
+          V: Deserialize<'de> + Default
+{
+    Ok(Option::<V>::deserialize(deserializer)?.unwrap_or_default())
+}
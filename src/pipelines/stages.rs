@@ -0,0 +1,276 @@
+use mongodb::bson::{doc, Bson, Document};
+
+use crate::pipelines::Pipeline;
+
+// Create accumulator used by `$group`, `$bucket`, `$bucketAuto` and `$setWindowFields`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Accumulator {
+    Sum(Bson),
+    Avg(Bson),
+    Min(Bson),
+    Max(Bson),
+    First(Bson),
+    Last(Bson),
+    Push(Bson),
+    AddToSet(Bson),
+    Count,
+}
+
+impl Accumulator {
+    pub fn to_document(&self) -> Document {
+        match self {
+            Self::Sum(expression) => doc! { "$sum": expression.clone() },
+            Self::Avg(expression) => doc! { "$avg": expression.clone() },
+            Self::Min(expression) => doc! { "$min": expression.clone() },
+            Self::Max(expression) => doc! { "$max": expression.clone() },
+            Self::First(expression) => doc! { "$first": expression.clone() },
+            Self::Last(expression) => doc! { "$last": expression.clone() },
+            Self::Push(expression) => doc! { "$push": expression.clone() },
+            Self::AddToSet(expression) => doc! { "$addToSet": expression.clone() },
+            Self::Count => doc! { "$sum": 1 },
+        }
+    }
+}
+
+// Create `$merge` behaviour when a matching document exists
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WhenMatched {
+    Replace,
+    KeepExisting,
+    #[default]
+    Merge,
+    Fail,
+}
+
+// Create `$merge` behaviour when no matching document exists
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WhenNotMatched {
+    #[default]
+    Insert,
+    Discard,
+    Fail,
+}
+
+// Converts (field, accumulator) pairs into an output document
+fn to_output<F>(fields: &[(F, Accumulator)]) -> Document
+    where F: ToString
+{
+    let mut output = Document::new();
+    for (field, accumulator) in fields {
+        output.insert(field.to_string(), accumulator.to_document());
+    }
+
+    output
+}
+
+impl Pipeline {
+    /// Groups by `id` (e.g. `"$status"` or a document of fields, `Bson::Null` for everything)
+    pub fn group<I, F>(&mut self, id: I, accumulators: &[(F, Accumulator)]) -> &mut Self
+        where I: Into<Bson>,
+              F: ToString
+    {
+        let mut group = doc! { "_id": id.into() };
+        group.extend(to_output(accumulators));
+
+        self.queries.push(doc! { "$group": group });
+
+        self
+    }
+
+    pub fn add_fields(&mut self, fields: Document) -> &mut Self {
+        self.queries.push(doc! { "$addFields": fields });
+
+        self
+    }
+
+    pub fn set(&mut self, fields: Document) -> &mut Self {
+        self.queries.push(doc! { "$set": fields });
+
+        self
+    }
+
+    pub fn unset<T>(&mut self, fields: &[T]) -> &mut Self
+        where T: ToString
+    {
+        if !fields.is_empty() {
+            self.queries.push(doc! {
+                "$unset": fields.iter().map(|f| f.to_string()).collect::<Vec<String>>()
+            });
+        }
+
+        self
+    }
+
+    /// Buckets documents by `group_by` between sorted `boundaries`, values outside them go to
+    /// `default` (the stage fails without one)
+    pub fn bucket<G, F>(&mut self, group_by: G, boundaries: Vec<Bson>, default: Option<Bson>, output: &[(F, Accumulator)]) -> &mut Self
+        where G: Into<Bson>,
+              F: ToString
+    {
+        let mut bucket = doc! {
+            "groupBy": group_by.into(),
+            "boundaries": boundaries
+        };
+
+        if let Some(default) = default {
+            bucket.insert("default", default);
+        }
+
+        if !output.is_empty() {
+            bucket.insert("output", to_output(output));
+        }
+
+        self.queries.push(doc! { "$bucket": bucket });
+
+        self
+    }
+
+    // Buckets documents into evenly distributed groups, granularity is e.g. "R5" or "POWERSOF2"
+    pub fn bucket_auto<G, F>(&mut self, group_by: G, buckets: i32, output: &[(F, Accumulator)], granularity: Option<&str>) -> &mut Self
+        where G: Into<Bson>,
+              F: ToString
+    {
+        let mut bucket = doc! {
+            "groupBy": group_by.into(),
+            "buckets": buckets.max(1)
+        };
+
+        if !output.is_empty() {
+            bucket.insert("output", to_output(output));
+        }
+
+        if let Some(granularity) = granularity {
+            bucket.insert("granularity", granularity);
+        }
+
+        self.queries.push(doc! { "$bucketAuto": bucket });
+
+        self
+    }
+
+    pub fn sample(&mut self, size: usize) -> &mut Self {
+        self.queries.push(doc! {
+            "$sample": { "size": size as i64 }
+        });
+
+        self
+    }
+
+    pub fn count<T>(&mut self, field: T) -> &mut Self
+        where T: ToString
+    {
+        self.queries.push(doc! { "$count": field.to_string() });
+
+        self
+    }
+
+    /// Appends documents of another collection, optionally run through its own pipeline
+    pub fn union_with<T>(&mut self, collection: T, pipeline: Option<&Pipeline>) -> &mut Self
+        where T: ToString
+    {
+        match pipeline {
+            Some(pipeline) => self.queries.push(doc! {
+                "$unionWith": {
+                    "coll": collection.to_string(),
+                    "pipeline": pipeline.build()
+                }
+            }),
+            None => self.queries.push(doc! {
+                "$unionWith": collection.to_string()
+            })
+        }
+
+        self
+    }
+
+    /// Window functions, output is (field, accumulator, window) where window is e.g.
+    /// `doc! { "documents": ["unbounded", "current"] }` for a running total
+    pub fn set_window_fields<P, F>(&mut self, partition_by: Option<P>, sort_by: Document, output: &[(F, Accumulator, Option<Document>)]) -> &mut Self
+        where P: Into<Bson>,
+              F: ToString
+    {
+        let mut fields = Document::new();
+        for (field, accumulator, window) in output {
+            let mut value = accumulator.to_document();
+            if let Some(window) = window {
+                value.insert("window", window.clone());
+            }
+
+            fields.insert(field.to_string(), value);
+        }
+
+        let mut stage = Document::new();
+        if let Some(partition_by) = partition_by {
+            stage.insert("partitionBy", partition_by.into());
+        }
+
+        if !sort_by.is_empty() {
+            stage.insert("sortBy", sort_by);
+        }
+
+        stage.insert("output", fields);
+
+        self.queries.push(doc! { "$setWindowFields": stage });
+
+        self
+    }
+
+    /// Pipeline form of `$lookup`, `let_vars` exposes local fields to the sub pipeline as `$$name`
+    pub fn lookup_pipeline<T, U>(&mut self, from: T, let_vars: Document, pipeline: &Pipeline, as_field: U) -> &mut Self
+        where T: ToString,
+              U: ToString
+    {
+        self.queries.push(doc! {
+            "$lookup": {
+                "from": from.to_string(),
+                "let": let_vars,
+                "pipeline": pipeline.build(),
+                "as": as_field.to_string()
+            }
+        });
+
+        self
+    }
+
+    /// Writes results into another collection, must be the last stage
+    pub fn merge<T, O>(&mut self, into: T, on: &[O], when_matched: WhenMatched, when_not_matched: WhenNotMatched) -> &mut Self
+        where T: ToString,
+              O: ToString
+    {
+        let when_matched = match when_matched {
+            WhenMatched::Replace => "replace",
+            WhenMatched::KeepExisting => "keepExisting",
+            WhenMatched::Merge => "merge",
+            WhenMatched::Fail => "fail",
+        };
+
+        let when_not_matched = match when_not_matched {
+            WhenNotMatched::Insert => "insert",
+            WhenNotMatched::Discard => "discard",
+            WhenNotMatched::Fail => "fail",
+        };
+
+        let mut merge = doc! {
+            "into": into.to_string(),
+            "whenMatched": when_matched,
+            "whenNotMatched": when_not_matched
+        };
+
+        if !on.is_empty() {
+            merge.insert("on", on.iter().map(|o| o.to_string()).collect::<Vec<String>>());
+        }
+
+        self.queries.push(doc! { "$merge": merge });
+
+        self
+    }
+
+    /// Replaces the collection with the results, must be the last stage
+    pub fn out<T>(&mut self, collection: T) -> &mut Self
+        where T: ToString
+    {
+        self.queries.push(doc! { "$out": collection.to_string() });
+
+        self
+    }
+}
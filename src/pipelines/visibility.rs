@@ -0,0 +1,130 @@
+use mongodb::bson::Bson;
+
+use crate::pipelines::Filter;
+
+/// Claims resolved by `PermissionGuard` implement this so a `Visibility` policy can scope
+/// queries to the caller. Both default to None
+pub trait VisibilityClaims {
+    fn get_role(&self) -> Option<String> {
+        None
+    }
+
+    fn get_tenant(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Visibility policy hides records by role, scopes them to the caller's tenant and leaves out
+/// soft deleted records. Every rule is off by default, e.g. hide a superuser role with
+/// `Visibility::builder().set_hidden_roles(&["Controller"])`. Build one per app and apply it with
+/// `Pipeline::visibility`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Visibility {
+    pub role_field: String,
+    pub hidden_roles: Vec<String>,
+    pub privileged_roles: Vec<String>,
+    pub tenant_field: Option<String>,
+    pub deleted_field: Option<String>,
+    pub is_include_deleted: bool,
+}
+
+impl Default for Visibility {
+    fn default() -> Self {
+        Self {
+            role_field: String::from("role"),
+            hidden_roles: Vec::new(),
+            privileged_roles: Vec::new(),
+            tenant_field: None,
+            deleted_field: None,
+            is_include_deleted: false,
+        }
+    }
+}
+
+impl Visibility {
+    pub fn builder() -> Self {
+        Self::default()
+    }
+
+    pub fn set_role_field<T>(&self, role_field: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.role_field = role_field.to_string();
+        data
+    }
+
+    // Records holding one of these roles are hidden from non privileged callers
+    pub fn set_hidden_roles<T>(&self, hidden_roles: &[T]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.hidden_roles = hidden_roles.iter().map(|r| r.to_string()).collect();
+        data
+    }
+
+    // Callers with one of these roles skip the hidden role and tenant rules
+    pub fn set_privileged_roles<T>(&self, privileged_roles: &[T]) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.privileged_roles = privileged_roles.iter().map(|r| r.to_string()).collect();
+        data
+    }
+
+    // Callers only see records whose tenant field equals their tenant, callers without one see nothing
+    pub fn set_tenant_field<T>(&self, tenant_field: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.tenant_field = Some(tenant_field.to_string());
+        data
+    }
+
+    // Records where this field is set (e.g. `deleted_at`) are left out
+    pub fn set_deleted_field<T>(&self, deleted_field: T) -> Self
+        where T: ToString
+    {
+        let mut data = self.clone();
+        data.deleted_field = Some(deleted_field.to_string());
+        data
+    }
+
+    pub fn set_include_deleted(&self, is_include_deleted: bool) -> Self {
+        let mut data = self.clone();
+        data.is_include_deleted = is_include_deleted;
+        data
+    }
+
+    /// Builds the filter for the caller, pass None for anonymous requests
+    pub fn to_filter<C>(&self, claims: Option<&C>) -> Filter
+        where C: VisibilityClaims
+    {
+        let role = claims.and_then(|claims| claims.get_role());
+        let tenant = claims.and_then(|claims| claims.get_tenant());
+        let is_privileged = role
+            .as_ref()
+            .map(|role| self.privileged_roles.contains(role))
+            .unwrap_or(false);
+
+        let mut filters = Vec::new();
+
+        if !is_privileged && !self.hidden_roles.is_empty() {
+            filters.push(Filter::negate(Filter::is_in(&self.role_field, self.hidden_roles.clone())));
+        }
+
+        if let Some(tenant_field) = self.tenant_field.as_ref().filter(|_| !is_privileged) {
+            match tenant {
+                Some(tenant) => filters.push(Filter::eq(tenant_field, tenant)),
+                None => filters.push(Filter::is_in(tenant_field, Vec::<String>::new()))
+            }
+        }
+
+        // `$eq: null` also matches missing fields
+        if let Some(deleted_field) = self.deleted_field.as_ref().filter(|_| !self.is_include_deleted) {
+            filters.push(Filter::eq(deleted_field, Bson::Null));
+        }
+
+        Filter::and(filters)
+    }
+}
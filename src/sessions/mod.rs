@@ -0,0 +1,174 @@
+pub mod queries;
+pub mod setters;
+
+use arraygen::Arraygen;
+use serde::{Serialize, Deserialize};
+
+pub static TABLE_SESSIONS: &str = "sessions";
+
+use crate::traits::prelude::*;
+use crate::MongoDateTime;
+use crate::MongoObjectId;
+use crate::Payload;
+use crate::Token;
+use crate::UserAgent;
+
+// Struct container for an authenticated session, one per refresh token
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, Arraygen)]
+#[gen_array(fn get_array_id: &mut Option<MongoObjectId>)]
+#[gen_array(fn get_array_date: &mut Option<MongoDateTime>)]
+pub struct Session {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    #[in_array(get_array_id)]
+    pub id: Option<MongoObjectId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub browser: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_new_device: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[in_array(get_array_date)]
+    pub first_seen_at: Option<MongoDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[in_array(get_array_date)]
+    pub last_seen_at: Option<MongoDateTime>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[in_array(get_array_date)]
+    pub revoked_at: Option<MongoDateTime>,
+}
+
+impl IsEmpty for Session {
+    fn is_empty(&self) -> bool {
+        Self::default() == *self
+    }
+}
+
+impl ToBson for Session {
+    fn to_bson(&self) -> Option<Self> {
+        let mut data = self.clone();
+
+        for id in data.get_array_id() {
+            *id = id.clone().and_then(|d| d.to_bson());
+        }
+
+        for date in data.get_array_date() {
+            *date = date.clone().and_then(|d| d.to_bson());
+        }
+
+        match data.is_empty() {
+            true => None,
+            false => Some(data)
+        }
+    }
+}
+
+impl ToJson for Session {
+    fn to_json(&self) -> Option<Self> {
+        let mut data = self.clone();
+
+        // Never expose token hashes
+        data.token = None;
+
+        for id in data.get_array_id() {
+            *id = id.clone().and_then(|d| d.to_json());
+        }
+
+        for date in data.get_array_date() {
+            *date = date.clone().and_then(|d| d.to_json());
+        }
+
+        match data.is_empty() {
+            true => None,
+            false => Some(data)
+        }
+    }
+}
+
+impl ToPayload for Session {
+    fn to_payload(&self, code: usize) -> Payload {
+        let data = self.to_json().unwrap_or_default();
+
+        match code {
+            200 => Payload::data(code, data),
+            _ => Payload {
+                code: Some(code as u16),
+                errors: Some(serde_json::to_value(data).unwrap_or(serde_json::Value::Null)),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates session for the given refresh token using the parsed user agent of the login request
+    pub fn from_user_agent<A, T>(account_id: A, refresh_token: T, user_agent: &UserAgent) -> Self
+        where A: ToString,
+              T: ToString
+    {
+        let browser = user_agent.product.name.clone().filter(|name| name != "Other");
+        let os = user_agent.os.name.clone().filter(|name| name != "Other");
+        let model = user_agent.device.model.clone().or(user_agent.device.name.clone()).filter(|name| name != "Other");
+
+        // Create display name e.g. "Chrome on Mac OS X"
+        let device = match (&browser, &os, &model) {
+            (Some(browser), Some(os), _) => Some(format!("{browser} on {os}")),
+            (Some(browser), None, Some(model)) => Some(format!("{browser} on {model}")),
+            (None, _, Some(model)) => Some(model.clone()),
+            (Some(browser), None, None) => Some(browser.clone()),
+            (None, Some(os), None) => Some(os.clone()),
+            (None, None, None) => None
+        };
+
+        // Versions are left out so browser and os updates don't look like a new device
+        let fingerprint = crate::ciphers::hash(format!(
+            "{}|{}|{}|{}",
+            browser.clone().unwrap_or_default(),
+            os.clone().unwrap_or_default(),
+            user_agent.device.brand.clone().unwrap_or_default(),
+            model.unwrap_or_default()
+        ).to_lowercase());
+
+        Self {
+            account_id: Some(account_id.to_string()),
+            token: Some(crate::ciphers::hash(refresh_token.to_string())),
+            fingerprint: Some(fingerprint),
+            device,
+            os,
+            browser,
+            ip: user_agent.ip.clone(),
+            ..Default::default()
+        }
+    }
+
+    // Creates session from tokens generated by `Paseto::generate_tokens`
+    pub fn from_token<A>(account_id: A, token: &Token, user_agent: &UserAgent) -> Self
+        where A: ToString
+    {
+        let mut session = Self::from_user_agent(account_id, token.refresh.clone().unwrap_or_default(), user_agent);
+
+        if token.refresh.clone().unwrap_or_default().trim().is_empty() {
+            session.token = None;
+        }
+
+        session
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
@@ -0,0 +1,39 @@
+use actix_web::Result;
+use mongodb::{bson::doc, Database};
+
+use crate::sessions::TABLE_SESSIONS;
+use crate::sessions::Session;
+
+use crate::traits::{ToBson, ToJson};
+
+use crate::Message;
+use crate::Payload;
+
+impl Session {
+    /// Registers the session of a freshly issued refresh token. The session is flagged with
+    /// `is_new_device` when the account never logged in from the same browser, os and device
+    pub async fn create(&self, database: &Database) -> Result<Self> {
+        let (account_id, fingerprint) = match (&self.account_id, &self.fingerprint, &self.token) {
+            (Some(account_id), Some(fingerprint), Some(_)) => (account_id.clone(), fingerprint.clone()),
+            _ => return Err(Payload::error(Message::new("session.save_failed")))
+        };
+
+        let collection = database.collection::<Session>(TABLE_SESSIONS);
+        let filter = doc! { "account_id": account_id, "fingerprint": fingerprint };
+
+        let mut session = self.set_insertable();
+        session.is_new_device = match collection.count_documents(filter, None).await {
+            Ok(count) => Some(count == 0),
+            Err(error) => return Err(Payload::error(error))
+        };
+
+        if let Some(value) = session.to_bson() {
+            return match collection.insert_one(value, None).await {
+                Ok(_) => Ok(session.to_json().unwrap_or_default()),
+                Err(error) => Err(Payload::error(error))
+            };
+        }
+
+        Err(Payload::error(Message::new("session.save_failed")))
+    }
+}
\ No newline at end of file
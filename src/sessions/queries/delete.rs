@@ -0,0 +1,70 @@
+use actix_web::Result;
+use mongodb::{bson::{doc, oid::ObjectId, Bson}, Database};
+use std::str::FromStr;
+
+use crate::sessions::TABLE_SESSIONS;
+use crate::sessions::Session;
+
+use crate::Message;
+use crate::MongoDateTime;
+use crate::Payload;
+
+impl Session {
+    /// Revokes a session of an account, e.g. from the "active sessions" list
+    pub async fn revoke<A, I>(database: &Database, account_id: A, session_id: I) -> Result<u64>
+        where A: ToString,
+              I: ToString
+    {
+        let session_id = match ObjectId::from_str(&session_id.to_string()) {
+            Ok(session_id) => session_id,
+            Err(_) => return Err(Payload::error(Message::new("request.invalid_object_id")))
+        };
+
+        let filter = doc! {
+            "_id": session_id,
+            "account_id": account_id.to_string(),
+            "revoked_at": { "$exists": false }
+        };
+
+        Self::revoke_many(database, filter).await
+    }
+
+    /// Revokes the session of a refresh token, e.g. on logout
+    pub async fn revoke_token<T>(database: &Database, refresh_token: T) -> Result<u64>
+        where T: ToString
+    {
+        let filter = doc! {
+            "token": crate::ciphers::hash(refresh_token),
+            "revoked_at": { "$exists": false }
+        };
+
+        Self::revoke_many(database, filter).await
+    }
+
+    /// Revokes every session of an account, optionally keeping the current one
+    pub async fn revoke_all<A, T>(database: &Database, account_id: A, except_refresh_token: Option<T>) -> Result<u64>
+        where A: ToString,
+              T: ToString
+    {
+        let mut filter = doc! {
+            "account_id": account_id.to_string(),
+            "revoked_at": { "$exists": false }
+        };
+
+        if let Some(refresh_token) = except_refresh_token {
+            filter.insert("token", doc! { "$ne": crate::ciphers::hash(refresh_token) });
+        }
+
+        Self::revoke_many(database, filter).await
+    }
+
+    async fn revoke_many(database: &Database, filter: mongodb::bson::Document) -> Result<u64> {
+        let collection = database.collection::<Session>(TABLE_SESSIONS);
+        let update = doc! { "$set": { "revoked_at": Bson::from(MongoDateTime::new()) } };
+
+        match collection.update_many(filter, update, None).await {
+            Ok(result) => Ok(result.modified_count),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+}
\ No newline at end of file
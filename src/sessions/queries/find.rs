@@ -0,0 +1,55 @@
+use actix_web::Result;
+use futures::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions, Database};
+
+use crate::sessions::TABLE_SESSIONS;
+use crate::sessions::Session;
+
+use crate::traits::ToJson;
+
+use crate::ApiError;
+use crate::Message;
+use crate::Payload;
+
+impl Session {
+    /// Retrieves the active (not revoked) session of a refresh token, without its token hash
+    pub async fn find_by_token<T>(database: &Database, refresh_token: T) -> Result<Self>
+        where T: ToString
+    {
+        let collection = database.collection::<Session>(TABLE_SESSIONS);
+        let filter = doc! {
+            "token": crate::ciphers::hash(refresh_token),
+            "revoked_at": { "$exists": false }
+        };
+
+        match collection.find_one(filter, None).await {
+            Ok(Some(session)) => Ok(session.to_json().unwrap_or_default()),
+            Ok(None) => Err(ApiError::AuthInvalid(Message::new("session.revoked").to_string()).into()),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+
+    /// Lists active sessions of an account, most recently used first
+    pub async fn list<T>(database: &Database, account_id: T) -> Result<Vec<Self>>
+        where T: ToString
+    {
+        let collection = database.collection::<Session>(TABLE_SESSIONS);
+        let filter = doc! {
+            "account_id": account_id.to_string(),
+            "revoked_at": { "$exists": false }
+        };
+        let options = FindOptions::builder()
+            .sort(doc! { "last_seen_at": -1 })
+            .build();
+
+        let cursor = match collection.find(filter, options).await {
+            Ok(cursor) => cursor,
+            Err(error) => return Err(Payload::error(error))
+        };
+
+        match cursor.try_collect::<Vec<Self>>().await {
+            Ok(sessions) => Ok(sessions.iter().filter_map(|session| session.to_json()).collect()),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+}
\ No newline at end of file
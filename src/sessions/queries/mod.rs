@@ -0,0 +1,4 @@
+pub mod create;
+pub mod delete;
+pub mod find;
+pub mod update;
\ No newline at end of file
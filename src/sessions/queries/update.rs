@@ -0,0 +1,49 @@
+use actix_web::Result;
+use mongodb::{bson::{doc, Bson, Document}, options::{FindOneAndUpdateOptions, ReturnDocument}, Database};
+
+use crate::sessions::TABLE_SESSIONS;
+use crate::sessions::Session;
+
+use crate::traits::ToJson;
+
+use crate::ApiError;
+use crate::Message;
+use crate::MongoDateTime;
+use crate::Payload;
+use crate::UserAgent;
+
+impl Session {
+    /// Marks the session of a refresh token as seen, rotating its token when a new refresh token
+    /// was issued. Fails when the session was revoked so the refresh can be rejected
+    pub async fn touch<T, N>(database: &Database, refresh_token: T, new_refresh_token: Option<N>, user_agent: &UserAgent) -> Result<Self>
+        where T: ToString,
+              N: ToString
+    {
+        let collection = database.collection::<Session>(TABLE_SESSIONS);
+        let filter = doc! {
+            "token": crate::ciphers::hash(refresh_token),
+            "revoked_at": { "$exists": false }
+        };
+
+        let mut set = Document::new();
+        set.insert("last_seen_at", Bson::from(MongoDateTime::new()));
+
+        if let Some(ip) = &user_agent.ip {
+            set.insert("ip", ip);
+        }
+
+        if let Some(new_refresh_token) = new_refresh_token {
+            set.insert("token", crate::ciphers::hash(new_refresh_token));
+        }
+
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+
+        match collection.find_one_and_update(filter, doc! { "$set": set }, options).await {
+            Ok(Some(session)) => Ok(session.to_json().unwrap_or_default()),
+            Ok(None) => Err(ApiError::AuthInvalid(Message::new("session.revoked").to_string()).into()),
+            Err(error) => Err(Payload::error(error))
+        }
+    }
+}
\ No newline at end of file
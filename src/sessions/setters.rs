@@ -0,0 +1,24 @@
+use crate::sessions::Session;
+
+use crate::MongoObjectId;
+use crate::MongoDateTime;
+
+impl Session {
+    pub fn set_insertable(&self) -> Self {
+        let mut session = self.clone();
+
+        if session.id.is_none() {
+            session.id = Some(MongoObjectId::new());
+        }
+
+        if session.first_seen_at.is_none() {
+            session.first_seen_at = Some(MongoDateTime::new());
+        }
+
+        if session.last_seen_at.is_none() {
+            session.last_seen_at = Some(MongoDateTime::new());
+        }
+
+        session
+    }
+}
\ No newline at end of file
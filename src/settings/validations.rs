@@ -1,5 +1,6 @@
 use actix_web::Result;
 
+use crate::Message;
 use crate::Payload;
 use crate::Primitive;
 use crate::Settings;
@@ -9,22 +10,22 @@ impl Settings {
     pub fn validate_base(&self) -> Result<Payload> {
         let api_url = crate::validate_string(&self.api_url, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("API URL cannot be empty".to_string()),
-                "invalid" => Some("API URL is invalid".to_string()),
+                "empty" => Some(Message::new("settings.api_url.empty").to_string()),
+                "invalid" => Some(Message::new("settings.api_url.invalid").to_string()),
                 _=> None
             });
 
         let web_url = crate::validate_string(&self.web_url, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Web URL cannot be empty".to_string()),
-                "invalid" => Some("Web URL is invalid".to_string()),
+                "empty" => Some(Message::new("settings.web_url.empty").to_string()),
+                "invalid" => Some(Message::new("settings.web_url.invalid").to_string()),
                 _=> None
             });
 
         let admin_url = crate::validate_string(&self.web_url, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Admin URL cannot be empty".to_string()),
-                "invalid" => Some("Admin URL is invalid".to_string()),
+                "empty" => Some(Message::new("settings.admin_url.empty").to_string()),
+                "invalid" => Some(Message::new("settings.admin_url.invalid").to_string()),
                 _=> None
             });
 
@@ -51,44 +52,44 @@ impl Settings {
 
         let access_token_key_unit = crate::validate_primitive_i32(&self.access_token_key_unit, Some(1))
             .and_then(|error| match error {
-                "empty" => Some(Primitive::from("Please enter an access token key unit")),
-                "invalid" => Some(Primitive::from("Please enter a valid access token key unit")),
+                "empty" => Some(Primitive::from(Message::new("settings.access_token_key_unit.empty").to_string())),
+                "invalid" => Some(Primitive::from(Message::new("settings.access_token_key_unit.invalid").to_string())),
                 _=> None
             });
 
         let access_token_key_time = crate::validate_string_options(&self.access_token_key_time, &options)
             .and_then(|error| match error {
-                "empty" => Some("Please enter your access token key time".to_string()),
-                "invalid" => Some("Please enter a valid access token key time".to_string()),
+                "empty" => Some(Message::new("settings.access_token_key_time.empty").to_string()),
+                "invalid" => Some(Message::new("settings.access_token_key_time.invalid").to_string()),
                 _=> None
             });
 
         let access_token_key_signing = crate::validate_string_base64(&self.access_token_key_signing, Some(32))
             .and_then(|error| match error {
-                "empty" => Some("Please enter your access token key signing".to_string()),
-                "invalid" => Some("Please enter a valid access token key signing".to_string()),
+                "empty" => Some(Message::new("settings.access_token_key_signing.empty").to_string()),
+                "invalid" => Some(Message::new("settings.access_token_key_signing.invalid").to_string()),
                 _=> None
             });
 
         let refresh_token_key_unit = crate::validate_primitive_i32(&self.refresh_token_key_unit, Some(1))
             .and_then(|error| match error {
-                "empty" => Some(Primitive::from("Please enter an refresh token key unit")),
-                "invalid" => Some(Primitive::from("Please enter a valid refresh token key unit")),
+                "empty" => Some(Primitive::from(Message::new("settings.refresh_token_key_unit.empty").to_string())),
+                "invalid" => Some(Primitive::from(Message::new("settings.refresh_token_key_unit.invalid").to_string())),
                 _=> None
             });
 
 
         let refresh_token_key_time = crate::validate_string_options(&self.refresh_token_key_time, &options)
             .and_then(|error| match error {
-                "empty" => Some("Please enter your refresh token key time".to_string()),
-                "invalid" => Some("Please enter a valid refresh token key time".to_string()),
+                "empty" => Some(Message::new("settings.refresh_token_key_time.empty").to_string()),
+                "invalid" => Some(Message::new("settings.refresh_token_key_time.invalid").to_string()),
                 _=> None
             });
 
         let refresh_token_key_signing = crate::validate_string_base64(&self.refresh_token_key_signing, Some(32))
             .and_then(|error| match error {
-                "empty" => Some("Please enter your refresh token key signing".to_string()),
-                "invalid" => Some("Please enter a valid refresh token key signing".to_string()),
+                "empty" => Some(Message::new("settings.refresh_token_key_signing.empty").to_string()),
+                "invalid" => Some(Message::new("settings.refresh_token_key_signing.invalid").to_string()),
                 _=> None
             });
 
@@ -111,43 +112,43 @@ impl Settings {
     pub fn validate_mailer(&self) -> Result<Payload> {
         let sender = crate::validate_string(&self.sender, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter the sender of your mailer".to_string()),
-                "invalid" => Some("Please enter a valid sender".to_string()),
+                "empty" => Some(Message::new("settings.sender.empty").to_string()),
+                "invalid" => Some(Message::new("settings.sender.invalid").to_string()),
                 _=> None
             });
 
         let username = crate::validate_string(&self.username, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter the mailer username".to_string()),
-                "invalid" => Some("Please enter a valid username".to_string()),
+                "empty" => Some(Message::new("settings.username.empty").to_string()),
+                "invalid" => Some(Message::new("settings.username.invalid").to_string()),
                 _=> None
             });
 
         let password = crate::validate_string(&self.password, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter the mailer password".to_string()),
-                "invalid" => Some("Please enter a valid password".to_string()),
+                "empty" => Some(Message::new("settings.password.empty").to_string()),
+                "invalid" => Some(Message::new("settings.password.invalid").to_string()),
                 _=> None
             });
 
         let smtp_host = crate::validate_string(&self.smtp_host, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter the mailer SMTP host".to_string()),
-                "invalid" => Some("Please enter a valid SMTP host".to_string()),
+                "empty" => Some(Message::new("settings.smtp_host.empty").to_string()),
+                "invalid" => Some(Message::new("settings.smtp_host.invalid").to_string()),
                 _=> None
             });
 
         let service = crate::validate_string(&self.service, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter the mailer service".to_string()),
-                "invalid" => Some("Please enter a valid mailer services".to_string()),
+                "empty" => Some(Message::new("settings.service.empty").to_string()),
+                "invalid" => Some(Message::new("settings.service.invalid").to_string()),
                 _=> None
             });
 
         let email = crate::validate_email(&self.email)
             .and_then(|error| match error {
-                "empty" => Some("Please enter the mailer service".to_string()),
-                "invalid" => Some("Please enter a valid mailer services".to_string()),
+                "empty" => Some(Message::new("settings.email.empty").to_string()),
+                "invalid" => Some(Message::new("settings.email.invalid").to_string()),
                 _=> None
             });
 
@@ -170,29 +171,29 @@ impl Settings {
     pub fn validate_s3(&self) -> Result<Payload> {
         let access_key_id = crate::validate_string(&self.access_key_id, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter an access key id".to_string()),
-                "invalid" => Some("Please enter a valid access key id".to_string()),
+                "empty" => Some(Message::new("settings.access_key_id.empty").to_string()),
+                "invalid" => Some(Message::new("settings.access_key_id.invalid").to_string()),
                 _=> None
             });
 
         let secret_access_key = crate::validate_string(&self.secret_access_key, Some(5))
             .and_then(|error| match error {
-                "empty" => Some("Please enter a secret access key".to_string()),
-                "invalid" => Some("Please enter a valid secret access key".to_string()),
+                "empty" => Some(Message::new("settings.secret_access_key.empty").to_string()),
+                "invalid" => Some(Message::new("settings.secret_access_key.invalid").to_string()),
                 _=> None
             });
 
         let bucket = crate::validate_string(&self.bucket, Some(2))
             .and_then(|error| match error {
-                "empty" => Some("Please enter a bucket name".to_string()),
-                "invalid" => Some("Please enter a valid bucket name".to_string()),
+                "empty" => Some(Message::new("settings.bucket.empty").to_string()),
+                "invalid" => Some(Message::new("settings.bucket.invalid").to_string()),
                 _=> None
             });
 
         let region = crate::validate_string(&self.region, Some(2))
             .and_then(|error| match error {
-                "empty" => Some("Please enter a region".to_string()),
-                "invalid" => Some("Please enter a valid region".to_string()),
+                "empty" => Some(Message::new("settings.region.empty").to_string()),
+                "invalid" => Some(Message::new("settings.region.invalid").to_string()),
                 _=> None
             });
 
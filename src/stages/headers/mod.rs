@@ -0,0 +1,13 @@
+use crate::middlewares::SecurityHeaders;
+
+/// Returns security headers setup, HSTS is only sent when served over https
+pub fn stage(is_https: bool) -> SecurityHeaders {
+    // Set bindings
+    let headers = SecurityHeaders::default();
+
+    // Return security headers
+    match is_https {
+        true => headers,
+        false => headers.set_hsts("")
+    }
+}
\ No newline at end of file
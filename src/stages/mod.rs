@@ -1,4 +1,5 @@
 pub mod cors;
 pub mod favicons;
 pub mod handlebars;
+pub mod headers;
 pub mod json;
\ No newline at end of file
@@ -1,7 +1,7 @@
 pub mod prelude;
 
 use chrono::{DateTime as ChronoDateTime, Utc};
-use mongodb::bson::{oid::ObjectId, DateTime as BsonDateTime};
+use mongodb::bson::{oid::ObjectId, Bson, DateTime as BsonDateTime};
 
 use crate::Payload;
 
@@ -17,6 +17,10 @@ pub trait Encrypt {
     fn encrypt(&self) -> Option<Self> where Self: Sized;
 }
 
+pub trait FromBson {
+    fn from_bson(value: &Bson) -> Option<Self> where Self: Sized;
+}
+
 pub trait GetArrayObject<T:Clone + GetObjectId + ToJson + ToBson + IsEmpty + PartialEq + Default> {
     fn get_array_object(&self) -> Option<Vec<T>> where T: Sized;
 }
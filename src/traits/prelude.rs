@@ -1,6 +1,7 @@
 pub use super::Decrypt;
 pub use super::Dedup;
 pub use super::Encrypt;
+pub use super::FromBson;
 pub use super::GetArrayObject;
 pub use super::GetArrayObjectId;
 pub use super::GetArrayString;
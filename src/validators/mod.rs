@@ -5,6 +5,7 @@ use validator::validate_email as validator_email;
 use crate::traits::GetI32;
 use crate::traits::IsEmpty;
 use crate::Cipher;
+use crate::Message;
 use crate::Primitive;
 
 pub fn validate_email<T>(value: &Option<T>) -> Option<&str>
@@ -62,12 +63,13 @@ pub fn validate_password<E, I>(input: &Option<Cipher>, strict: bool, min: usize,
     let empty = empty.to_string();
     let invalid = invalid.to_string();
 
-    let error_min = format!("minimum of {} characters allowed", min.clone());
-    let error_max = format!("maximum of {} characters allowed", max.clone());
-    let error_number = String::from("at least 1 number");
-    let error_uppercase = String::from("at least 1 uppercase character");
-    let error_lowercase = String::from("at least 1 lowercase character");
-    let error_special_character = String::from("at least 1 special character");
+    // Rule messages are keys rendered in the negotiated locale when the response is built
+    let error_min = Message::new("password.minimum").set_arg("min", min).to_string();
+    let error_max = Message::new("password.maximum").set_arg("max", max).to_string();
+    let error_number = Message::new("password.number").to_string();
+    let error_uppercase = Message::new("password.uppercase").to_string();
+    let error_lowercase = Message::new("password.lowercase").to_string();
+    let error_special_character = Message::new("password.special_character").to_string();
 
     match strict {
         true => {